@@ -0,0 +1,231 @@
+use crate::{
+    contracts::{
+        AccessControlledAggregator::AccessControlledAggregator,
+        EACAggregatorProxy::EACAggregatorProxy, OffchainAggregator::OffchainAggregator,
+    },
+    rpc::reverted_as_none,
+    FeedReader, Result, RoundData,
+};
+use alloy_primitives::{Address, I256, U256};
+
+/// Where the bounds were read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsKind {
+    /// OCR aggregator `minAnswer` / `maxAnswer`
+    Answer,
+    /// flux aggregator `minSubmissionValue` / `maxSubmissionValue`
+    Submission,
+}
+
+/// Range the aggregator clamps answers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnswerBounds {
+    pub aggregator: Address,
+    pub min: I256,
+    pub max: I256,
+    pub kind: BoundsKind,
+}
+
+/// Position of an answer relative to the aggregator bounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundStatus {
+    Within,
+    NearMin,
+    NearMax,
+    /// pinned: the real price is probably lower
+    AtMin,
+    /// pinned: the real price is probably higher
+    AtMax,
+}
+
+impl BoundStatus {
+    /// Answer is clamped to a bound
+    pub fn is_pinned(&self) -> bool {
+        matches!(self, BoundStatus::AtMin | BoundStatus::AtMax)
+    }
+}
+
+impl AnswerBounds {
+    /// Classifies `answer`, near = within `tolerance_bps` of the bound value
+    /// ie: 100 bps flags answers within 1% of min / max
+    pub fn classify(&self, answer: I256, tolerance_bps: u32) -> BoundStatus {
+        if answer <= self.min {
+            return BoundStatus::AtMin;
+        }
+        if answer >= self.max {
+            return BoundStatus::AtMax;
+        }
+        if answer.saturating_sub(self.min) <= margin(self.min, tolerance_bps) {
+            return BoundStatus::NearMin;
+        }
+        if self.max.saturating_sub(answer) <= margin(self.max, tolerance_bps) {
+            return BoundStatus::NearMax;
+        }
+        BoundStatus::Within
+    }
+}
+
+/// |bound| * bps / 10_000
+fn margin(bound: I256, bps: u32) -> I256 {
+    let bps = I256::from_raw(U256::from(bps));
+    bound.saturating_abs().saturating_mul(bps) / I256::from_raw(U256::from(10_000))
+}
+
+/// Latest round together with the bounds of the aggregator that produced it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitCheck {
+    pub round: RoundData,
+    pub bounds: AnswerBounds,
+    pub status: BoundStatus,
+}
+
+impl FeedReader {
+    /// Aggregator currently behind the proxy
+    pub async fn aggregator(&self) -> Result<Address> {
+        let call = EACAggregatorProxy::aggregatorCall {};
+        Ok(self.rpc.call(self.proxy, &call, self.block).await?._0)
+    }
+
+    /// Bounds of the current aggregator, OCR `minAnswer`/`maxAnswer` first
+    /// then flux `minSubmissionValue`/`maxSubmissionValue`
+    /// None when the aggregator exposes neither (ie: legacy aggregators)
+    pub async fn answer_bounds(&self) -> Result<Option<AnswerBounds>> {
+        let aggregator = self.aggregator().await?;
+        self.bounds_of(aggregator).await
+    }
+
+    pub(crate) async fn bounds_of(&self, aggregator: Address) -> Result<Option<AnswerBounds>> {
+        let (rpc, block) = (&self.rpc, self.block);
+        let min_call = OffchainAggregator::minAnswerCall {};
+        if let Some(min) = reverted_as_none(rpc.call(aggregator, &min_call, block).await)? {
+            let max_call = OffchainAggregator::maxAnswerCall {};
+            let max = rpc.call(aggregator, &max_call, block).await?;
+            return Ok(Some(AnswerBounds {
+                aggregator,
+                min: min._0,
+                max: max._0,
+                kind: BoundsKind::Answer,
+            }));
+        }
+        let min_call = AccessControlledAggregator::minSubmissionValueCall {};
+        if let Some(min) = reverted_as_none(rpc.call(aggregator, &min_call, block).await)? {
+            let max_call = AccessControlledAggregator::maxSubmissionValueCall {};
+            let max = rpc.call(aggregator, &max_call, block).await?;
+            return Ok(Some(AnswerBounds {
+                aggregator,
+                min: min._0,
+                max: max._0,
+                kind: BoundsKind::Submission,
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Reads the latest round and flags answers at or within `tolerance_bps` of the bounds
+    /// None when the aggregator has no bounds to compare with
+    pub async fn circuit_breaker(&self, tolerance_bps: u32) -> Result<Option<CircuitCheck>> {
+        let Some(bounds) = self.answer_bounds().await? else {
+            return Ok(None);
+        };
+        let round = self.latest_round_data().await?;
+        Ok(Some(CircuitCheck {
+            round,
+            bounds,
+            status: bounds.classify(round.answer, tolerance_bps),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        testing::{call_args, output, reverted, MockNode},
+        Error,
+    };
+    use alloy_primitives::address;
+    use alloy_sol_types::SolCall;
+    use serde_json::json;
+
+    const PROXY: Address = address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
+
+    fn max() -> I256 {
+        I256::try_from(i64::MAX).unwrap()
+    }
+
+    /// proxy whose aggregator answers `min_answer` to `minAnswer` and has no flux bounds
+    async fn aggregator_node(min_answer: fn() -> Result<Vec<u8>, (i64, String)>) -> MockNode {
+        MockNode::start(move |_, params| {
+            let (to, data) = call_args(params);
+            let selector: [u8; 4] = data[..4].try_into().unwrap();
+            if to == PROXY {
+                assert_eq!(selector, EACAggregatorProxy::aggregatorCall::SELECTOR);
+                let aggregator = Address::repeat_byte(7);
+                return Ok(output(
+                    EACAggregatorProxy::aggregatorCall::abi_encode_returns(&(aggregator,)),
+                ));
+            }
+            match selector {
+                OffchainAggregator::minAnswerCall::SELECTOR => min_answer().map(output),
+                OffchainAggregator::maxAnswerCall::SELECTOR => Ok(output(
+                    OffchainAggregator::maxAnswerCall::abi_encode_returns(&(max(),)),
+                )),
+                // a contract without the function nor a fallback: plain "0x"
+                _ => Ok(json!("0x")),
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn bounds() {
+        let node = aggregator_node(|| {
+            let min = I256::ONE;
+            Ok(OffchainAggregator::minAnswerCall::abi_encode_returns(&(
+                min,
+            )))
+        })
+        .await;
+        let bounds = FeedReader::new(&node.url, PROXY)
+            .answer_bounds()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bounds.kind, BoundsKind::Answer);
+        assert_eq!((bounds.min, bounds.max), (I256::ONE, max()));
+
+        let node = aggregator_node(|| Err(reverted())).await;
+        let reader = FeedReader::new(&node.url, PROXY);
+        assert_eq!(reader.answer_bounds().await.unwrap(), None);
+
+        let node = aggregator_node(|| Err((-32005, "limit exceeded".to_string()))).await;
+        let reader = FeedReader::new(&node.url, PROXY);
+        assert!(matches!(
+            reader.answer_bounds().await,
+            Err(Error::Rpc { code: -32005, .. })
+        ));
+
+        // output that is there but doesn't decode is not a missing function
+        let node = aggregator_node(|| Ok(vec![1, 2, 3])).await;
+        let reader = FeedReader::new(&node.url, PROXY);
+        assert!(matches!(reader.answer_bounds().await, Err(Error::Abi(_))));
+    }
+
+    #[test]
+    fn classify() {
+        let bounds = AnswerBounds {
+            aggregator: Address::ZERO,
+            min: I256::try_from(1_000).unwrap(),
+            max: I256::try_from(100_000).unwrap(),
+            kind: BoundsKind::Answer,
+        };
+        let status = |answer: i64| bounds.classify(I256::try_from(answer).unwrap(), 100);
+        assert_eq!(status(999), BoundStatus::AtMin);
+        assert_eq!(status(1_000), BoundStatus::AtMin);
+        assert_eq!(status(1_010), BoundStatus::NearMin);
+        assert_eq!(status(50_000), BoundStatus::Within);
+        assert_eq!(status(99_000), BoundStatus::NearMax);
+        assert_eq!(status(100_000), BoundStatus::AtMax);
+        assert!(status(100_000).is_pinned());
+    }
+}
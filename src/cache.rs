@@ -0,0 +1,328 @@
+use crate::{error, unix_now, write_atomic, Error, Oracle, OraclesIndex, ReferenceSource, Result};
+use alloy_chains::Chain;
+use reqwest::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// On-disk cache of reference feeds, one body + metadata file per chain
+/// revalidates with ETag / Last-Modified and falls back to the stored copy when offline
+#[derive(Debug, Clone)]
+pub struct FeedsCache {
+    pub dir: PathBuf,
+    pub source: ReferenceSource,
+}
+
+/// Validators and timestamps stored next to the cached body
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CacheMeta {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    /// unix seconds when the body was downloaded
+    pub fetched_at: u64,
+    /// unix seconds when the server last confirmed the body (200 or 304)
+    pub validated_at: u64,
+}
+
+/// How the returned snapshot was obtained
+#[derive(Debug)]
+pub enum SnapshotOrigin {
+    /// fresh body from the server
+    Downloaded,
+    /// server answered 304, cached body still current
+    NotModified,
+    /// request failed, serving the cached body
+    Fallback(Error),
+}
+
+/// Reference feeds as served by the cache
+#[derive(Debug)]
+pub struct Snapshot {
+    pub body: String,
+    pub meta: CacheMeta,
+    pub origin: SnapshotOrigin,
+}
+
+impl Snapshot {
+    /// Time since the server last confirmed this body
+    pub fn age(&self) -> Duration {
+        let validated = UNIX_EPOCH + Duration::from_secs(self.meta.validated_at);
+        SystemTime::now()
+            .duration_since(validated)
+            .unwrap_or_default()
+    }
+
+    pub fn is_fallback(&self) -> bool {
+        matches!(self.origin, SnapshotOrigin::Fallback(_))
+    }
+}
+
+impl FeedsCache {
+    /// Cache in `dir` fetching from the default reference directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            source: ReferenceSource::default(),
+        }
+    }
+
+    pub fn with_source(mut self, source: ReferenceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    fn body_path(&self, chain: Chain) -> PathBuf {
+        self.dir.join(format!("feeds-{}.json", chain.id()))
+    }
+
+    fn meta_path(&self, chain: Chain) -> PathBuf {
+        self.dir.join(format!("feeds-{}.meta.json", chain.id()))
+    }
+
+    /// Returns the stored snapshot of a chain without touching the network
+    pub fn cached(&self, chain: Chain) -> Option<(String, CacheMeta)> {
+        let body = fs::read_to_string(self.body_path(chain)).ok()?;
+        let meta = fs::read_to_string(self.meta_path(chain)).ok()?;
+        let meta: CacheMeta = serde_json::from_str(&meta).ok()?;
+        Some((body, meta))
+    }
+
+    /// Body first: a crash in between leaves old validators, which only cost a download
+    fn store(&self, chain: Chain, body: &str, meta: &CacheMeta) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        write_atomic(&self.body_path(chain), body.as_bytes())?;
+        let meta = serde_json::to_vec(meta).map_err(std::io::Error::from)?;
+        write_atomic(&self.meta_path(chain), &meta)?;
+        Ok(())
+    }
+
+    /// Fetches the feeds of a chain, sending a conditional request when a copy is stored
+    /// network failures (transport errors, 5xx) are answered with the cached copy when there
+    /// is one, other errors (4xx, a body that won't decode..) are returned as is
+    /// copies downloaded from another url than the current one are ignored
+    pub async fn fetch(&self, chain: Chain) -> Result<Snapshot> {
+        let (url, req) = self.source.request(chain)?;
+        // files are keyed by chain only, the source may have changed since
+        let cached = self.cached(chain).filter(|(_, meta)| meta.url == url);
+        match self.revalidate(url, req, cached.as_ref().map(|(_, m)| m)).await {
+            Ok(Some((body, meta))) => {
+                self.store(chain, &body, &meta)?;
+                Ok(Snapshot { body, meta, origin: SnapshotOrigin::Downloaded })
+            }
+            Ok(None) => {
+                // 304 is only accepted with validators from a cached copy
+                let Some((body, mut meta)) = cached else {
+                    return Err(Error::InvalidResponse(
+                        "304 Not Modified without a cached copy".to_string(),
+                    ));
+                };
+                meta.validated_at = unix_now();
+                self.store(chain, &body, &meta)?;
+                Ok(Snapshot { body, meta, origin: SnapshotOrigin::NotModified })
+            }
+            Err(e) => match cached {
+                Some((body, meta)) if is_network_failure(&e) => {
+                    Ok(Snapshot { body, meta, origin: SnapshotOrigin::Fallback(e) })
+                }
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Returns None when the server says the cached copy (from `url`) is current
+    async fn revalidate(
+        &self,
+        url: String,
+        mut req: reqwest::RequestBuilder,
+        meta: Option<&CacheMeta>,
+    ) -> Result<Option<(String, CacheMeta)>> {
+        if let Some(meta) = meta {
+            if let Some(etag) = &meta.etag {
+                req = req.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &meta.last_modified {
+                req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = req.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED && meta.is_some() {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Error::HttpStatus { status, url });
+        }
+        let headers = response.headers();
+        let etag = header_string(headers, header::ETAG);
+        let last_modified = header_string(headers, header::LAST_MODIFIED);
+        let body = response.text().await?;
+        // don't poison the cache with a body that won't load
+        error::decode::<Vec<Oracle>>(&body)?;
+        let now = unix_now();
+        let meta = CacheMeta {
+            url,
+            etag,
+            last_modified,
+            fetched_at: now,
+            validated_at: now,
+        };
+        Ok(Some((body, meta)))
+    }
+
+    /// Loads the index of a chain through the cache
+    pub async fn load(&self, chain: Chain) -> Result<(OraclesIndex, Snapshot)> {
+        let snapshot = self.fetch(chain).await?;
+        let index = OraclesIndex::from_json_str(chain, &snapshot.body)?;
+        Ok((index, snapshot))
+    }
+}
+
+/// Failures a later request may not hit, a misconfigured url or a broken directory
+/// would otherwise be hidden behind the cached copy forever
+fn is_network_failure(e: &Error) -> bool {
+    match e {
+        Error::Transport(_) => true,
+        Error::HttpStatus { status, .. } => status.is_server_error(),
+        _ => false,
+    }
+}
+
+fn header_string(headers: &header::HeaderMap, name: header::HeaderName) -> Option<String> {
+    headers.get(name)?.to_str().ok().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{serve_http, HttpResponse};
+    use std::sync::{Arc, Mutex};
+
+    const FEEDS: &str = r#"[{"pair": ["ETH", "USD"]}]"#;
+
+    /// (status, body) the server answers with
+    type Answer = Arc<Mutex<(u16, &'static str)>>;
+
+    fn answer(status: u16, body: &'static str) -> Answer {
+        Arc::new(Mutex::new((status, body)))
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("datafeeds-cache-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Serves `answer` tagged with the body length, 304 when a 200 would send the etag
+    /// already held
+    async fn server(answer: Answer) -> String {
+        serve_http(move |request| {
+            assert_eq!(request.method, "GET");
+            assert_eq!(request.path, "/feeds-mainnet.json");
+            let (status, body) = *answer.lock().unwrap();
+            let etag = format!("\"{}\"", body.len());
+            if status == 200 && request.header("if-none-match") == Some(etag.as_str()) {
+                return HttpResponse {
+                    status: 304,
+                    headers: Vec::new(),
+                    body: String::new(),
+                };
+            }
+            HttpResponse {
+                status,
+                headers: vec![("etag", etag)],
+                body: body.to_string(),
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn revalidates_and_falls_back() {
+        let dir = temp_dir("revalidate");
+        let answer = answer(200, FEEDS);
+        let url = server(answer.clone()).await;
+        let cache = FeedsCache::new(&dir).with_source(ReferenceSource::new(&url));
+        let chain = Chain::mainnet();
+
+        let snapshot = cache.fetch(chain).await.unwrap();
+        assert!(matches!(snapshot.origin, SnapshotOrigin::Downloaded));
+        assert_eq!(snapshot.meta.url, format!("{url}/feeds-mainnet.json"));
+        assert_eq!(snapshot.meta.etag, Some(format!("\"{}\"", FEEDS.len())));
+
+        let snapshot = cache.fetch(chain).await.unwrap();
+        assert!(matches!(snapshot.origin, SnapshotOrigin::NotModified));
+        assert_eq!(snapshot.body, cache.cached(chain).unwrap().0);
+
+        *answer.lock().unwrap() = (500, "");
+        let snapshot = cache.fetch(chain).await.unwrap();
+        assert!(snapshot.is_fallback());
+        let (index, _) = cache.load(chain).await.unwrap();
+        assert_eq!(index.feeds.len(), 1);
+
+        // only the renamed files are left
+        let mut files: Vec<_> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(files, ["feeds-1.json", "feeds-1.meta.json"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn ignores_copies_of_another_source() {
+        let dir = temp_dir("source");
+        let chain = Chain::mainnet();
+        let url = server(answer(200, FEEDS)).await;
+        FeedsCache::new(&dir)
+            .with_source(ReferenceSource::new(&url))
+            .fetch(chain)
+            .await
+            .unwrap();
+
+        // same chain from another mirror: no validators sent, no fallback
+        let down = answer(500, "");
+        let other =
+            FeedsCache::new(&dir).with_source(ReferenceSource::new(server(down.clone()).await));
+        assert!(matches!(
+            other.fetch(chain).await,
+            Err(Error::HttpStatus { status, .. }) if status == StatusCode::INTERNAL_SERVER_ERROR
+        ));
+
+        *down.lock().unwrap() = (200, FEEDS);
+        let snapshot = other.fetch(chain).await.unwrap();
+        assert!(matches!(snapshot.origin, SnapshotOrigin::Downloaded));
+        assert_ne!(snapshot.meta.url, format!("{url}/feeds-mainnet.json"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn no_fallback_on_client_errors() {
+        let dir = temp_dir("client-errors");
+        let chain = Chain::mainnet();
+        let answer = answer(200, FEEDS);
+        let cache =
+            FeedsCache::new(&dir).with_source(ReferenceSource::new(server(answer.clone()).await));
+        cache.fetch(chain).await.unwrap();
+
+        *answer.lock().unwrap() = (404, "not found");
+        assert!(matches!(
+            cache.fetch(chain).await,
+            Err(Error::HttpStatus { status, .. }) if status == StatusCode::NOT_FOUND
+        ));
+        // `pair` is missing
+        *answer.lock().unwrap() = (200, r#"[{"name": "ETH / USD"}]"#);
+        assert!(matches!(
+            cache.fetch(chain).await,
+            Err(Error::Decode { .. })
+        ));
+        // the copy is kept for later
+        assert_eq!(cache.cached(chain).unwrap().0, FEEDS);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,9 @@
+use alloy_sol_types::sol;
+
+// only the bits of AccessControlledOffchainAggregator not covered by the other bindings
+// bounds are int192 on chain, decoded as int256 (same abi word, sign extended)
+sol!(
+    OffchainAggregator,
+    r#"[{"inputs":[],"name":"minAnswer","outputs":[{"internalType":"int192","name":"","type":"int256"}],"stateMutability":"view","type":"function"},{"inputs":[],"name":"maxAnswer","outputs":[{"internalType":"int192","name":"","type":"int256"}],"stateMutability":"view","type":"function"}]
+    "#
+);
@@ -1,4 +1,6 @@
 #![allow(non_snake_case)]
 pub mod EACAggregatorProxy;
 pub mod AggregatorContract;
-pub mod AccessControlledAggregator;
\ No newline at end of file
+pub mod AccessControlledAggregator;
+pub mod OffchainAggregator;
+pub mod Multicall3;
@@ -0,0 +1,264 @@
+use crate::{
+    Error, FeedFilter, FeedReader, Oracle, OraclesIndex, Price, Result, RoundData, RpcClient,
+};
+use alloy_primitives::{I256, U256};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// One hop of a cross rate path
+#[derive(Debug, Clone, Copy)]
+pub struct Leg<'a> {
+    pub oracle: &'a Oracle,
+    /// answer is used as 1 / answer (feed quotes the hop backwards)
+    pub inverse: bool,
+}
+
+impl<'a> Leg<'a> {
+    /// (from, to) symbols of the hop
+    pub fn symbols(&self) -> Option<(&'a str, &'a str)> {
+        let (token, base) = self.oracle.symbols()?;
+        Some(if self.inverse { (base, token) } else { (token, base) })
+    }
+}
+
+/// Derived price of `from` in `to` and how it was obtained
+#[derive(Debug, Clone)]
+pub struct CrossRate<'a> {
+    pub price: Price,
+    pub path: Vec<Leg<'a>>,
+    /// rounds read for each leg, same order as `path`
+    pub rounds: Vec<RoundData>,
+    /// staleness of the derived price is that of its oldest leg
+    pub oldest_updated_at: u64,
+}
+
+/// Graph of pairs over an index, finds and evaluates conversion paths
+/// ie: LINK / EUR from LINK / USD and EUR / USD (inverted)
+#[derive(Debug, Clone)]
+pub struct CrossRates<'a> {
+    index: &'a OraclesIndex,
+    edges: HashMap<String, Vec<(String, Leg<'a>)>>,
+}
+
+impl<'a> CrossRates<'a> {
+    /// Uses every feed of the index that has a proxy
+    pub fn new(index: &'a OraclesIndex) -> Self {
+        Self::build(index, None)
+    }
+
+    /// Only feeds matching `filter` are used as legs (ie: exclude deprecated ones)
+    pub fn with_filter(index: &'a OraclesIndex, filter: &FeedFilter) -> Self {
+        Self::build(index, Some(filter))
+    }
+
+    fn build(index: &'a OraclesIndex, filter: Option<&FeedFilter>) -> Self {
+        let mut edges: HashMap<String, Vec<(String, Leg<'a>)>> = HashMap::new();
+        let feeds = index
+            .feeds
+            .iter()
+            .filter(|o| o.proxy_address.is_some())
+            .filter(|o| filter.is_none_or(|f| f.matches(o)));
+        for oracle in feeds {
+            let Some((token, base)) = oracle.symbols() else {
+                continue;
+            };
+            let (token, base) = (token.to_uppercase(), base.to_uppercase());
+            edges
+                .entry(token.clone())
+                .or_default()
+                .push((base.clone(), Leg { oracle, inverse: false }));
+            edges
+                .entry(base)
+                .or_default()
+                .push((token, Leg { oracle, inverse: true }));
+        }
+        Self { index, edges }
+    }
+
+    pub fn index(&self) -> &'a OraclesIndex {
+        self.index
+    }
+
+    /// Shortest path (fewest feeds) converting `from` into `to`, direct feeds win ties
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<Leg<'a>>> {
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        if from == to {
+            return None;
+        }
+        let mut previous: HashMap<&str, (&str, Leg<'a>)> = HashMap::new();
+        let mut seen: HashSet<&str> = HashSet::from([from.as_str()]);
+        let mut queue = VecDeque::from([from.as_str()]);
+        while let Some(node) = queue.pop_front() {
+            let mut next: Vec<&(String, Leg<'a>)> = self.edges.get(node)?.iter().collect();
+            next.sort_by_key(|(_, leg)| leg.inverse);
+            for (target, leg) in next {
+                if !seen.insert(target.as_str()) {
+                    continue;
+                }
+                previous.insert(target.as_str(), (node, *leg));
+                if *target == to {
+                    let mut path = Vec::new();
+                    let mut cursor = target.as_str();
+                    while let Some((prev, leg)) = previous.get(cursor) {
+                        path.push(*leg);
+                        cursor = prev;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back(target.as_str());
+            }
+        }
+        None
+    }
+
+    /// Fetches the rounds of every leg and returns the derived `from / to` price
+    /// precision is the highest of the legs decimals
+    pub async fn derive(&self, rpc: &RpcClient, from: &str, to: &str) -> Result<CrossRate<'a>> {
+        let path = self.path(from, to).ok_or_else(|| Error::NoRoute {
+            from: from.to_string(),
+            to: to.to_string(),
+        })?;
+        let mut rounds = Vec::with_capacity(path.len());
+        let mut prices = Vec::with_capacity(path.len());
+        for leg in &path {
+            let proxy = leg.oracle.proxy_address.ok_or_else(|| Error::MissingProxy {
+                name: leg.oracle.name.clone(),
+            })?;
+            let reader = FeedReader::with_client(rpc.clone(), proxy);
+            let round = reader.latest_round_data().await?;
+            let decimals = match leg.oracle.decimals {
+                Some(d) => d,
+                None => reader.decimals().await?,
+            };
+            rounds.push(round);
+            prices.push(Price::from_round(&round, decimals));
+        }
+        let price = combine(&path, &prices)?;
+        let oldest_updated_at = rounds.iter().map(|r| r.updated_at).min().unwrap_or_default();
+        Ok(CrossRate {
+            price,
+            path,
+            rounds,
+            oldest_updated_at,
+        })
+    }
+}
+
+/// Multiplies the leg prices (inverting where needed) keeping an exact fraction until the end
+fn combine(path: &[Leg<'_>], prices: &[Price]) -> Result<Price> {
+    let decimals = prices.iter().map(|p| p.decimals).max().unwrap_or_default();
+    let mut num = U256::from(1);
+    let mut den = U256::from(1);
+    for (leg, price) in path.iter().zip(prices) {
+        if price.answer <= I256::ZERO {
+            return Err(Error::InvalidAnswer {
+                feed: leg.oracle.name.clone(),
+                answer: price.answer,
+            });
+        }
+        let answer = price.answer.unsigned_abs();
+        let scale = exp10(price.decimals)?;
+        let (n, d) = if leg.inverse { (scale, answer) } else { (answer, scale) };
+        num = num.checked_mul(n).ok_or(Error::Overflow)?;
+        den = den.checked_mul(d).ok_or(Error::Overflow)?;
+    }
+    let answer = num.checked_mul(exp10(decimals)?).ok_or(Error::Overflow)? / den;
+    let answer = I256::try_from(answer).map_err(|_| Error::Overflow)?;
+    Ok(Price::new(answer, decimals))
+}
+
+fn exp10(n: u8) -> Result<U256> {
+    U256::from(10).checked_pow(U256::from(n)).ok_or(Error::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+
+    fn index() -> OraclesIndex {
+        let json = r#"[
+            {"name": "LINK / USD", "pair": ["LINK", "USD"], "decimals": 8,
+             "proxyAddress": "0x2c1d072e956AFFC0D435Cb7AC38EF18d24d9127c"},
+            {"name": "EUR / USD", "pair": ["EUR", "USD"], "decimals": 8,
+             "proxyAddress": "0xb49f677943BC038e9857d61E7d053CaA2C1734C1"},
+            {"name": "LINK / ETH", "pair": ["LINK", "ETH"], "decimals": 18,
+             "proxyAddress": "0xDC530D9457755926550b59e8ECcdaE7624181557"},
+            {"name": "LINK / EUR", "pair": ["LINK", "EUR"], "decimals": 8}
+        ]"#;
+        OraclesIndex::from_json_str(Chain::mainnet(), json).unwrap()
+    }
+
+    fn price(answer: i64, decimals: u8) -> Price {
+        Price::new(I256::try_from(answer).unwrap(), decimals)
+    }
+
+    fn names(path: &[Leg<'_>]) -> Vec<(String, bool)> {
+        path.iter()
+            .map(|leg| (leg.oracle.name.clone().unwrap(), leg.inverse))
+            .collect()
+    }
+
+    #[test]
+    fn paths() {
+        let index = index();
+        let rates = CrossRates::new(&index);
+        // the LINK / EUR feed has no proxy
+        let path = rates.path("link", "eur").unwrap();
+        assert_eq!(
+            names(&path),
+            [
+                ("LINK / USD".to_string(), false),
+                ("EUR / USD".to_string(), true)
+            ]
+        );
+        assert_eq!(path[1].symbols(), Some(("USD", "EUR")));
+        assert_eq!(
+            names(&rates.path("USD", "LINK").unwrap()),
+            [("LINK / USD".to_string(), true)]
+        );
+        assert!(rates.path("LINK", "link").is_none());
+        assert!(rates.path("LINK", "JPY").is_none());
+
+        let rates = CrossRates::with_filter(&index, &FeedFilter::new().decimals(18));
+        assert!(rates.path("LINK", "EUR").is_none());
+    }
+
+    #[test]
+    fn combine() {
+        let index = index();
+        let rates = CrossRates::new(&index);
+        let path = rates.path("LINK", "EUR").unwrap();
+        // 15 / 1.08, truncated
+        let linkeur =
+            super::combine(&path, &[price(15_00000000, 8), price(1_08000000, 8)]).unwrap();
+        assert_eq!(linkeur, price(13_88888888, 8));
+
+        // highest precision of the legs, eth / usd = (link / usd) / (link / eth)
+        let path = rates.path("ETH", "USD").unwrap();
+        assert_eq!(
+            names(&path),
+            [
+                ("LINK / ETH".to_string(), true),
+                ("LINK / USD".to_string(), false)
+            ]
+        );
+        let ethusd = super::combine(
+            &path,
+            &[price(5_000000000000000, 18), price(15_00000000, 8)],
+        )
+        .unwrap();
+        assert_eq!(ethusd.to_string(), "3000.000000000000000000");
+
+        assert!(matches!(
+            super::combine(&path, &[price(0, 18), price(15_00000000, 8)]),
+            Err(Error::InvalidAnswer { feed: Some(name), .. }) if name == "LINK / ETH"
+        ));
+        let max = Price::new(I256::MAX, 0);
+        assert!(matches!(super::combine(&path[1..], &[max]), Ok(p) if p == max));
+        assert!(matches!(
+            super::combine(&[path[1], path[1]], &[max, max]),
+            Err(Error::Overflow)
+        ));
+    }
+}
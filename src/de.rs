@@ -0,0 +1,132 @@
+// Serde helpers for directory fields that come as numbers or strings
+// and json-rpc hex quantities
+
+use alloy_primitives::U256;
+use rust_decimal::Decimal;
+use serde::{de::Error, Deserialize, Deserializer};
+use std::str::FromStr;
+
+/// Raw text of a json number / string field, empty strings are treated as missing
+fn raw_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let raw = match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => return Ok(None),
+        // integers keep their digits, other numbers went through f64 and come back as the
+        // shortest text of that f64 (`1e18` for 1000000000000000000.0, digits past f64
+        // precision lost), `parse_decimal` / `parse_u256` read both forms
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => s.trim().to_string(),
+        other => {
+            return Err(D::Error::custom(format!(
+                "expected a number, got {}",
+                other
+            )))
+        }
+    };
+    Ok(Some(raw).filter(|s| !s.is_empty()))
+}
+
+/// Parses a decimal keeping every digit written (no f64 rounding)
+pub(crate) fn parse_decimal(s: &str) -> Option<Decimal> {
+    Decimal::from_str(s)
+        .or_else(|_| Decimal::from_scientific(s))
+        .ok()
+}
+
+/// Integer written out or in exponent form (`1e18`), no fractional part
+pub(crate) fn parse_u256(s: &str) -> Option<U256> {
+    if let Ok(n) = U256::from_str(s) {
+        return Some(n);
+    }
+    let d = parse_decimal(s)?;
+    if d.is_sign_negative() || !d.fract().is_zero() {
+        return None;
+    }
+    U256::from_str(&d.trunc().to_string()).ok()
+}
+
+pub(crate) fn decimal_opt<'de, D>(deserializer: D) -> Result<Option<Decimal>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    raw_number(deserializer)?
+        .map(|s| {
+            parse_decimal(&s).ok_or_else(|| D::Error::custom(format!("invalid decimal `{}`", s)))
+        })
+        .transpose()
+}
+
+pub(crate) fn u256_opt<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    raw_number(deserializer)?
+        .map(|s| parse_u256(&s).ok_or_else(|| D::Error::custom(format!("invalid integer `{}`", s))))
+        .transpose()
+}
+
+/// Json-rpc hex quantity ("0x1b4")
+pub(crate) fn quantity<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    crate::rpc::parse_quantity(&s).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Fields {
+        #[serde(default, deserialize_with = "decimal_opt")]
+        decimal: Option<Decimal>,
+        #[serde(default, deserialize_with = "u256_opt")]
+        integer: Option<U256>,
+    }
+
+    fn fields(json: &str) -> Result<Fields, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    #[test]
+    fn numbers_and_strings() {
+        let f = fields(r#"{"decimal": 0.5, "integer": 100000000}"#).unwrap();
+        assert_eq!(f.decimal, Some(Decimal::new(5, 1)));
+        assert_eq!(f.integer, Some(U256::from(100_000_000u64)));
+
+        let f = fields(r#"{"decimal": "0.123456789012345678901", "integer": "1000000000000000000000000000000"}"#).unwrap();
+        assert_eq!(f.decimal, Decimal::from_str("0.123456789012345678901").ok());
+        assert_eq!(f.integer, Some(U256::from(10u64).pow(U256::from(30))));
+
+        let f = fields(r#"{"decimal": "", "integer": null}"#).unwrap();
+        assert_eq!((f.decimal, f.integer), (None, None));
+        let f = fields("{}").unwrap();
+        assert_eq!((f.decimal, f.integer), (None, None));
+    }
+
+    #[test]
+    fn exponents() {
+        let wei = U256::from(10u64).pow(U256::from(18));
+        for json in [
+            r#"{"integer": 1e18}"#,
+            r#"{"integer": 1000000000000000000.0}"#,
+            r#"{"integer": "1e18"}"#,
+            r#"{"integer": "1E+18"}"#,
+        ] {
+            assert_eq!(fields(json).unwrap().integer, Some(wei), "{}", json);
+        }
+        let f = fields(r#"{"decimal": 2.5e-3}"#).unwrap();
+        assert_eq!(f.decimal, Some(Decimal::new(25, 4)));
+
+        for json in [
+            r#"{"integer": 1.5}"#,
+            r#"{"integer": -1}"#,
+            r#"{"integer": "abc"}"#,
+            r#"{"integer": true}"#,
+        ] {
+            assert!(fields(json).is_err(), "{}", json);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::kinds::{FeedCategory, FeedType, MarketHours};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+/// Chainlink reference-data-directory docs
+/// keys not modelled here are kept in `extra`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Docs {
+    pub asset_class: Option<String>,              //"Fiat",
+    pub asset_sub_class: Option<String>,          //"Stablecoin",
+    pub asset_name: Option<String>,               //"Japanese Yen",
+    pub base_asset: Option<String>,               //"JPY",
+    pub base_asset_clic: Option<String>,          //"JPY_FX",
+    pub quote_asset: Option<String>,              //"USD",
+    pub quote_asset_clic: Option<String>,         //"USD_FX"
+    pub blockchain_name: Option<String>,          //"Ethereum",
+    pub clic_product_name: Option<String>,        //"JPY/USD-RefPrice-DF-Ethereum-001",
+    pub delivery_channel_code: Option<String>,    //"DF",
+    pub feed_category: Option<FeedCategory>,      //"verified",
+    pub feed_type: Option<FeedType>,              //"Forex",
+    pub market_hours: Option<MarketHours>,        //"Forex",
+    pub product_sub_type: Option<String>,         //"Reference",
+    pub product_type: Option<String>,             //"Price",
+    pub product_type_code: Option<String>,        //"RefPrice",
+    pub shutdown_date: Option<String>,            //"March 31, 2024",
+    pub hidden: Option<bool>,
+    pub por_auditor: Option<String>,
+    pub por_source: Option<String>,
+    pub por_type: Option<String>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl Docs {
+    /// Feed has a shutdown date announced
+    pub fn is_deprecated(&self) -> bool {
+        self.shutdown_date.is_some()
+    }
+
+    /// Returns an unmodelled key
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.extra.get(key)
+    }
+}
+
+/// Some entries ship `"docs": null`
+pub(crate) fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
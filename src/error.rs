@@ -0,0 +1,105 @@
+use crate::validate::RoundIssue;
+use alloy_chains::Chain;
+use alloy_primitives::I256;
+use reqwest::StatusCode;
+
+/// Crate errors
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// no reference directory known for this chain
+    #[error("network not supported: {0}")]
+    UnsupportedChain(Chain),
+    /// request could not be sent or the body could not be read
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+    /// server answered with a non-success status
+    #[error("http status {status} from {url}")]
+    HttpStatus { status: StatusCode, url: String },
+    /// websocket connection failed or dropped (boxed, it is much larger than the rest)
+    #[error("websocket error: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    /// json-rpc node answered with an error object
+    #[error("rpc error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    /// json-rpc answer is not what the method should return
+    #[error("invalid rpc response: {0}")]
+    InvalidResponse(String),
+    /// call output doesn't match the contract abi
+    #[error("abi error: {0}")]
+    Abi(#[from] alloy_sol_types::Error),
+    /// call returned nothing: no contract at `to`, or no such function and no fallback
+    #[error("empty output from {to}")]
+    EmptyOutput { to: alloy_primitives::Address },
+    /// directory entry has no proxy to read from
+    #[error("no proxy address for feed {name:?}")]
+    MissingProxy { name: Option<String> },
+    /// no chain of feeds converts `from` into `to`
+    #[error("no route from {from} to {to}")]
+    NoRoute { from: String, to: String },
+    /// answer can't be used in a computation (ie: inverting a non-positive price)
+    #[error("invalid answer {answer} from feed {feed:?}")]
+    InvalidAnswer { feed: Option<String>, answer: I256 },
+    /// round rejected by a `RoundPolicy`
+    #[error("round {round_id} rejected: {issue}")]
+    InvalidRound { round_id: u128, issue: RoundIssue },
+    /// staleness grace factor that isn't a positive number
+    #[error("invalid grace factor {0}, expected a positive number")]
+    InvalidGraceFactor(rust_decimal::Decimal),
+    /// requested time is earlier than the first round of the feed
+    #[error("timestamp {timestamp} precedes the first round of the feed")]
+    BeforeFirstRound {
+        timestamp: u64,
+        first_updated_at: Option<u64>,
+    },
+    /// a block already confirmed (and its logs emitted) is no longer canonical
+    #[error("confirmed block {block_number} was reorged out")]
+    ReorgTooDeep { block_number: u64 },
+    /// a search used up its rpc call budget
+    #[error("gave up after {0} rpc calls")]
+    CallBudgetExceeded(usize),
+    /// intermediate value exceeds 256 bits
+    #[error("arithmetic overflow")]
+    Overflow,
+    /// local snapshot could not be read
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    /// body is not the expected json, `path` points to the offending field
+    #[error("could not decode json at `{path}`: {source}")]
+    Decode {
+        path: String,
+        source: serde_json::Error,
+    },
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(e))
+    }
+}
+
+/// Deserializes a json body, keeping track of the path that failed
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(body: &str) -> Result<T> {
+    decode_with(&mut serde_json::Deserializer::from_str(body))
+}
+
+/// Same as [`decode`] but streaming from a reader
+pub(crate) fn decode_reader<T, R>(rdr: R) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    R: std::io::Read,
+{
+    decode_with(&mut serde_json::Deserializer::from_reader(rdr))
+}
+
+fn decode_with<'de, T, D>(de: D) -> Result<T>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de, Error = serde_json::Error>,
+{
+    serde_path_to_error::deserialize(de).map_err(|e| Error::Decode {
+        path: e.path().to_string(),
+        source: e.into_inner(),
+    })
+}
@@ -0,0 +1,242 @@
+use crate::{ContractType, FeedCategory, FeedType, Oracle, OraclesIndex};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Inclusive bounds, a missing side is unbounded
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct Bounds<T> {
+    pub min: Option<T>,
+    pub max: Option<T>,
+}
+
+impl<T: PartialOrd> Bounds<T> {
+    pub fn contains(&self, value: &T) -> bool {
+        self.min.as_ref().is_none_or(|min| value >= min)
+            && self.max.as_ref().is_none_or(|max| value <= max)
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+}
+
+/// Composable criteria over the feeds of an index
+/// empty lists / unbounded ranges match anything, every set criterion has to match
+/// serializable so it can live in config files
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FeedFilter {
+    pub categories: Vec<FeedCategory>,
+    pub feed_types: Vec<FeedType>,
+    pub contract_types: Vec<ContractType>,
+    /// compared case-insensitively against docs `quoteAsset` or the pair
+    pub quote_assets: Vec<String>,
+    /// compared case-insensitively against docs `baseAsset` or the pair
+    pub base_assets: Vec<String>,
+    /// seconds
+    pub heartbeat: Bounds<u32>,
+    /// percent, same unit as `Oracle::threshold`
+    pub deviation_threshold: Bounds<Decimal>,
+    pub decimals: Vec<u8>,
+    pub has_history: Option<bool>,
+    /// drop feeds with a shutdown date or in the deprecating category
+    pub exclude_deprecated: bool,
+}
+
+impl FeedFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn category(mut self, category: FeedCategory) -> Self {
+        self.categories.push(category);
+        self
+    }
+
+    pub fn feed_type(mut self, feed_type: FeedType) -> Self {
+        self.feed_types.push(feed_type);
+        self
+    }
+
+    pub fn contract_type(mut self, contract_type: ContractType) -> Self {
+        self.contract_types.push(contract_type);
+        self
+    }
+
+    pub fn quote_asset(mut self, asset: impl Into<String>) -> Self {
+        self.quote_assets.push(asset.into());
+        self
+    }
+
+    pub fn base_asset(mut self, asset: impl Into<String>) -> Self {
+        self.base_assets.push(asset.into());
+        self
+    }
+
+    pub fn heartbeat_min(mut self, seconds: u32) -> Self {
+        self.heartbeat.min = Some(seconds);
+        self
+    }
+
+    pub fn heartbeat_max(mut self, seconds: u32) -> Self {
+        self.heartbeat.max = Some(seconds);
+        self
+    }
+
+    pub fn deviation_min(mut self, percent: Decimal) -> Self {
+        self.deviation_threshold.min = Some(percent);
+        self
+    }
+
+    pub fn deviation_max(mut self, percent: Decimal) -> Self {
+        self.deviation_threshold.max = Some(percent);
+        self
+    }
+
+    pub fn decimals(mut self, decimals: u8) -> Self {
+        self.decimals.push(decimals);
+        self
+    }
+
+    pub fn has_history(mut self, history: bool) -> Self {
+        self.has_history = Some(history);
+        self
+    }
+
+    pub fn exclude_deprecated(mut self) -> Self {
+        self.exclude_deprecated = true;
+        self
+    }
+
+    /// True if the oracle meets every set criterion
+    /// a criterion on a field the oracle doesn't have never matches
+    pub fn matches(&self, oracle: &Oracle) -> bool {
+        let category = oracle.feed_category.as_ref().or(oracle.docs.feed_category.as_ref());
+        let feed_type = oracle.feed_type.as_ref().or(oracle.docs.feed_type.as_ref());
+        let symbols = oracle.symbols();
+        let quote = oracle.docs.quote_asset.as_deref().or(symbols.map(|(_, b)| b));
+        let base = oracle.docs.base_asset.as_deref().or(symbols.map(|(t, _)| t));
+
+        any_of(&self.categories, category)
+            && any_of(&self.feed_types, feed_type)
+            && any_of(&self.contract_types, oracle.contract_type.as_ref())
+            && any_of(&self.decimals, oracle.decimals.as_ref())
+            && any_asset(&self.quote_assets, quote)
+            && any_asset(&self.base_assets, base)
+            && within(&self.heartbeat, oracle.heartbeat)
+            && within(&self.deviation_threshold, oracle.threshold)
+            && self.has_history.is_none_or(|h| oracle.history.unwrap_or(false) == h)
+            && !(self.exclude_deprecated && is_deprecated(oracle, category))
+    }
+
+    /// Oracles of the index matching the filter
+    pub fn apply<'a>(&'a self, index: &'a OraclesIndex) -> impl Iterator<Item = &'a Oracle> {
+        index.feeds.iter().filter(move |o| self.matches(o))
+    }
+}
+
+impl OraclesIndex {
+    /// Filtered view of the feeds, see `FeedFilter`
+    pub fn filter<'a>(&'a self, filter: &'a FeedFilter) -> impl Iterator<Item = &'a Oracle> {
+        filter.apply(self)
+    }
+}
+
+fn any_of<T: PartialEq>(wanted: &[T], value: Option<&T>) -> bool {
+    wanted.is_empty() || value.is_some_and(|v| wanted.contains(v))
+}
+
+fn any_asset(wanted: &[String], value: Option<&str>) -> bool {
+    wanted.is_empty()
+        || value.is_some_and(|v| wanted.iter().any(|w| w.eq_ignore_ascii_case(v)))
+}
+
+fn within<T: PartialOrd>(bounds: &Bounds<T>, value: Option<T>) -> bool {
+    bounds.is_unbounded() || value.is_some_and(|v| bounds.contains(&v))
+}
+
+fn is_deprecated(oracle: &Oracle, category: Option<&FeedCategory>) -> bool {
+    oracle.docs.is_deprecated() || category == Some(&FeedCategory::Deprecating)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+
+    fn index() -> OraclesIndex {
+        let json = r#"[
+            {"name": "ETH / USD", "pair": ["ETH", "USD"], "heartbeat": 3600, "threshold": 0.5,
+             "feedCategory": "low", "feedType": "Crypto", "decimals": 8, "history": true},
+            {"name": "JPY / USD", "pair": ["JPY", "USD"], "heartbeat": 86400, "threshold": 0.15,
+             "feedType": "Forex", "decimals": 8,
+             "docs": {"feedCategory": "low", "shutdownDate": "March 31, 2024"}},
+            {"name": "BTC / ETH", "pair": ["BTC", "ETH"], "heartbeat": 86400,
+             "feedCategory": "medium", "decimals": 18}
+        ]"#;
+        OraclesIndex::from_json_str(Chain::mainnet(), json).unwrap()
+    }
+
+    fn names(index: &OraclesIndex, filter: &FeedFilter) -> Vec<String> {
+        index
+            .filter(filter)
+            .filter_map(|o| o.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn criteria() {
+        let index = index();
+        assert_eq!(names(&index, &FeedFilter::new()).len(), 3);
+        // category read from docs when the top level one is missing
+        let low = FeedFilter::new().category(FeedCategory::Low);
+        assert_eq!(names(&index, &low), ["ETH / USD", "JPY / USD"]);
+        assert_eq!(
+            names(&index, &low.clone().exclude_deprecated()),
+            ["ETH / USD"]
+        );
+        assert_eq!(
+            names(
+                &index,
+                &FeedFilter::new().quote_asset("usd").heartbeat_min(7200)
+            ),
+            ["JPY / USD"]
+        );
+        // no threshold never matches a bounded deviation
+        let deviation = FeedFilter::new().deviation_max(Decimal::ONE);
+        assert_eq!(names(&index, &deviation), ["ETH / USD", "JPY / USD"]);
+        assert_eq!(
+            names(&index, &FeedFilter::new().has_history(false).decimals(8)),
+            ["JPY / USD"]
+        );
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let filter = FeedFilter::new()
+            .category(FeedCategory::Low)
+            .category(FeedCategory::Unknown("sponsored".to_string()))
+            .feed_type(FeedType::CryptoDex)
+            .quote_asset("USD")
+            .heartbeat_max(3600)
+            .deviation_min(Decimal::new(5, 1))
+            .has_history(true)
+            .exclude_deprecated();
+        let json = serde_json::to_string(&filter).unwrap();
+        assert_eq!(serde_json::from_str::<FeedFilter>(&json).unwrap(), filter);
+
+        // every field is optional in config files
+        let filter: FeedFilter = serde_json::from_str(
+            r#"{"categories": ["new token"], "heartbeat": {"max": 3600}, "excludeDeprecated": true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            filter,
+            FeedFilter::new()
+                .category(FeedCategory::NewToken)
+                .heartbeat_max(3600)
+                .exclude_deprecated()
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{rpc::BlockTag, Error, FeedReader, Oracle, Result, RoundData};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+/// Verdict of comparing a round's `updatedAt` with the feed heartbeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// updated within the allowed age
+    Fresh { age: u64, max_age: u64 },
+    /// older than heartbeat * grace factor
+    Stale { age: u64, max_age: u64 },
+    /// `updatedAt` is 0, round never got an answer
+    NeverUpdated,
+    /// `updatedAt` is later than the reference time (beyond the tolerated skew)
+    InFuture { ahead: u64 },
+    /// neither the feed nor the policy provide a heartbeat
+    UnknownHeartbeat { age: u64 },
+}
+
+impl Freshness {
+    pub fn is_fresh(&self) -> bool {
+        matches!(self, Freshness::Fresh { .. })
+    }
+}
+
+/// How strict a freshness check is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalenessPolicy {
+    /// allowed age = heartbeat * grace_factor rounded down (ie: 1.1 gives 10% of slack)
+    pub grace_factor: Decimal,
+    /// heartbeat (seconds) used when the directory has none
+    pub default_heartbeat: Option<u32>,
+    /// seconds `updatedAt` may be ahead of the reference time (clock skew)
+    pub future_tolerance: u64,
+}
+
+impl Default for StalenessPolicy {
+    fn default() -> Self {
+        Self {
+            grace_factor: Decimal::ONE,
+            default_heartbeat: None,
+            future_tolerance: 0,
+        }
+    }
+}
+
+impl StalenessPolicy {
+    /// Fails unless `grace_factor` is positive
+    pub fn with_grace_factor(mut self, grace_factor: Decimal) -> Result<Self> {
+        if grace_factor <= Decimal::ZERO {
+            return Err(Error::InvalidGraceFactor(grace_factor));
+        }
+        self.grace_factor = grace_factor;
+        Ok(self)
+    }
+
+    pub fn with_default_heartbeat(mut self, seconds: u32) -> Self {
+        self.default_heartbeat = Some(seconds);
+        self
+    }
+
+    pub fn with_future_tolerance(mut self, seconds: u64) -> Self {
+        self.future_tolerance = seconds;
+        self
+    }
+
+    /// Max accepted age for a heartbeat (0 when a non positive factor was set on the field)
+    pub fn max_age(&self, heartbeat: u32) -> u64 {
+        match Decimal::from(heartbeat).checked_mul(self.grace_factor) {
+            Some(max_age) => max_age.floor().to_u64().unwrap_or(0),
+            None => u64::MAX,
+        }
+    }
+
+    /// Evaluates `round` at time `now` (unix seconds, wall clock or block timestamp)
+    pub fn evaluate(&self, round: &RoundData, heartbeat: Option<u32>, now: u64) -> Freshness {
+        if round.updated_at == 0 {
+            return Freshness::NeverUpdated;
+        }
+        if round.updated_at > now {
+            let ahead = round.updated_at - now;
+            if ahead > self.future_tolerance {
+                return Freshness::InFuture { ahead };
+            }
+        }
+        let age = now.saturating_sub(round.updated_at);
+        let Some(heartbeat) = heartbeat.or(self.default_heartbeat) else {
+            return Freshness::UnknownHeartbeat { age };
+        };
+        let max_age = self.max_age(heartbeat);
+        if age > max_age {
+            Freshness::Stale { age, max_age }
+        } else {
+            Freshness::Fresh { age, max_age }
+        }
+    }
+
+    /// Same as `evaluate` with the heartbeat of a directory entry
+    pub fn evaluate_for(&self, round: &RoundData, oracle: &Oracle, now: u64) -> Freshness {
+        self.evaluate(round, oracle.heartbeat, now)
+    }
+}
+
+impl RoundData {
+    /// Freshness against the local clock with the default policy
+    pub fn freshness(&self, heartbeat: u32) -> Freshness {
+        StalenessPolicy::default().evaluate(self, Some(heartbeat), crate::unix_now())
+    }
+}
+
+impl FeedReader {
+    /// Reads the latest round and judges it against the timestamp of the block it was read at
+    pub async fn latest_round_checked(
+        &self,
+        heartbeat: Option<u32>,
+        policy: &StalenessPolicy,
+    ) -> Result<(RoundData, Freshness)> {
+        let block = match self.block {
+            BlockTag::Number(n) => BlockTag::Number(n),
+            // pin the block so the round and the timestamp come from the same state
+            _ => BlockTag::Number(self.rpc.block_number().await?),
+        };
+        let round = self.clone().at(block).latest_round_data().await?;
+        let now = self.rpc.block_timestamp(block).await?;
+        Ok((round, policy.evaluate(&round, heartbeat, now)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::I256;
+
+    fn round(updated_at: u64) -> RoundData {
+        RoundData {
+            round_id: 1,
+            answer: I256::ONE,
+            started_at: updated_at,
+            updated_at,
+            answered_in_round: 1,
+        }
+    }
+
+    #[test]
+    fn grace_factor() {
+        let policy = StalenessPolicy::default()
+            .with_grace_factor(Decimal::new(11, 1))
+            .unwrap();
+        assert_eq!(policy.max_age(3_600), 3_960);
+        assert_eq!(
+            policy.evaluate(&round(10_000), Some(3_600), 13_960),
+            Freshness::Fresh {
+                age: 3_960,
+                max_age: 3_960,
+            }
+        );
+        assert_eq!(
+            policy.evaluate(&round(10_000), Some(3_600), 13_961),
+            Freshness::Stale {
+                age: 3_961,
+                max_age: 3_960,
+            }
+        );
+        // fractions of a second are not granted
+        let policy = StalenessPolicy::default()
+            .with_grace_factor(Decimal::new(1_0001, 4))
+            .unwrap();
+        assert_eq!(policy.max_age(3_600), 3_600);
+
+        for invalid in [Decimal::ZERO, Decimal::NEGATIVE_ONE] {
+            assert!(matches!(
+                StalenessPolicy::default().with_grace_factor(invalid),
+                Err(Error::InvalidGraceFactor(_))
+            ));
+        }
+        let policy = StalenessPolicy {
+            grace_factor: Decimal::MAX,
+            ..StalenessPolicy::default()
+        };
+        assert_eq!(policy.max_age(u32::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn evaluate() {
+        let policy = StalenessPolicy::default().with_future_tolerance(5);
+        assert_eq!(
+            policy.evaluate(&round(0), Some(60), 100),
+            Freshness::NeverUpdated
+        );
+        assert_eq!(
+            policy.evaluate(&round(110), Some(60), 100),
+            Freshness::InFuture { ahead: 10 }
+        );
+        assert!(policy.evaluate(&round(103), Some(60), 100).is_fresh());
+        assert_eq!(
+            policy.evaluate(&round(50), None, 100),
+            Freshness::UnknownHeartbeat { age: 50 }
+        );
+        assert!(policy
+            .with_default_heartbeat(50)
+            .evaluate(&round(50), None, 100)
+            .is_fresh());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    phases::{compose_round_id, Phase},
+    rpc::BlockTag,
+    Error, FeedReader, Result, RoundData,
+};
+
+/// Default cap of `getRoundData` calls for a timestamp lookup
+/// (~20 phases + 2 * log2(2^64) rounds is far below)
+pub const DEFAULT_MAX_CALLS: usize = 256;
+
+/// Counts rpc calls of a search so it can't run away
+struct Budget {
+    left: usize,
+    max: usize,
+}
+
+impl Budget {
+    fn new(max: usize) -> Self {
+        Self { left: max, max }
+    }
+
+    async fn round(&mut self, reader: &FeedReader, round_id: u128) -> Result<Option<RoundData>> {
+        if self.left == 0 {
+            return Err(Error::CallBudgetExceeded(self.max));
+        }
+        self.left -= 1;
+        reader.try_round_data(round_id).await
+    }
+}
+
+impl FeedReader {
+    /// Round that was current at `block` (needs an archive node for old blocks)
+    pub async fn round_at_block(&self, block: u64) -> Result<RoundData> {
+        self.clone().at(BlockTag::Number(block)).latest_round_data().await
+    }
+
+    /// Round that was current at `timestamp` (unix seconds), see `round_at_timestamp_with`
+    pub async fn round_at_timestamp(&self, timestamp: u64) -> Result<RoundData> {
+        self.round_at_timestamp_with(timestamp, DEFAULT_MAX_CALLS).await
+    }
+
+    /// Picks the phase active at `timestamp` then binary searches its rounds for the last one
+    /// updated at or before it
+    /// at most `max_calls` `getRoundData` calls are made (phase discovery not included)
+    /// missing rounds (reverted or zeroed) inside a phase are treated as too recent, so gaps
+    /// can only make the answer an older round, never a later one
+    /// any other failure (rate limit, pinned block the node has no state for..) aborts the
+    /// search instead of passing for a missing round
+    pub async fn round_at_timestamp_with(
+        &self,
+        timestamp: u64,
+        max_calls: usize,
+    ) -> Result<RoundData> {
+        let phases = self.phases().await?;
+        let mut budget = Budget::new(max_calls);
+
+        // newest phase whose first round is not after `timestamp`
+        let mut found: Option<(Phase, RoundData)> = None;
+        let mut first_updated_at = None;
+        for phase in phases.iter().rev() {
+            let Some(first_id) = phase.first_round_id() else {
+                continue;
+            };
+            let Some(first) = budget.round(self, first_id).await? else {
+                continue;
+            };
+            first_updated_at = Some(first.updated_at);
+            if first.updated_at <= timestamp {
+                found = Some((*phase, first));
+                break;
+            }
+        }
+        let Some((phase, first)) = found else {
+            return Err(Error::BeforeFirstRound {
+                timestamp,
+                first_updated_at,
+            });
+        };
+
+        // invariant: `best` (aggregator round `lo`) was updated at or before `timestamp`
+        let (mut lo, mut hi) = (1u64, phase.latest_round);
+        let mut best = first;
+        while lo < hi {
+            let mid = lo + (hi - lo).div_ceil(2);
+            match budget.round(self, compose_round_id(phase.id, mid)).await? {
+                Some(round) if round.updated_at <= timestamp => {
+                    lo = mid;
+                    best = round;
+                }
+                _ => hi = mid - 1,
+            }
+        }
+        self.check(&best)?;
+        Ok(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contracts::EACAggregatorProxy::EACAggregatorProxy,
+        testing::{call_args, MockFeed, MockNode},
+    };
+    use alloy_primitives::{address, Address};
+    use alloy_sol_types::SolCall;
+    use serde_json::Value;
+
+    fn feed() -> MockFeed {
+        MockFeed {
+            proxy: address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            phases: vec![
+                (Address::repeat_byte(1), vec![100, 200, 300]),
+                (Address::repeat_byte(2), vec![]),
+                (
+                    Address::repeat_byte(3),
+                    (0..40).map(|i| 1000 + i * 10).collect(),
+                ),
+            ],
+        }
+    }
+
+    /// aggregator round of a `getRoundData` call, None for other calls
+    fn round_of(params: &Value) -> Option<u64> {
+        let (_, data) = call_args(params);
+        let call = EACAggregatorProxy::getRoundDataCall::abi_decode(&data, true).ok()?;
+        Some(call._roundId as u64)
+    }
+
+    #[tokio::test]
+    async fn finds_rounds_across_phases() {
+        let mock = feed();
+        let proxy = mock.proxy;
+        let node = MockNode::start(move |_, params| mock.eth_call(params)).await;
+        let reader = FeedReader::new(&node.url, proxy);
+        let at = |timestamp| reader.round_at_timestamp(timestamp);
+
+        assert_eq!(at(100).await.unwrap().round_id, compose_round_id(1, 1));
+        assert_eq!(at(299).await.unwrap().round_id, compose_round_id(1, 2));
+        // the empty phase 2 is skipped, phase 1 lasts until phase 3 starts
+        assert_eq!(at(999).await.unwrap().round_id, compose_round_id(1, 3));
+        assert_eq!(at(1000).await.unwrap().round_id, compose_round_id(3, 1));
+        assert_eq!(at(1255).await.unwrap().round_id, compose_round_id(3, 26));
+        assert_eq!(
+            at(u64::MAX).await.unwrap().round_id,
+            compose_round_id(3, 40)
+        );
+        assert!(matches!(
+            at(99).await,
+            Err(Error::BeforeFirstRound {
+                timestamp: 99,
+                first_updated_at: Some(100),
+            })
+        ));
+        assert!(matches!(
+            reader.round_at_timestamp_with(1255, 3).await,
+            Err(Error::CallBudgetExceeded(3))
+        ));
+    }
+
+    #[tokio::test]
+    async fn missing_rounds_narrow_the_search() {
+        let mock = feed();
+        let proxy = mock.proxy;
+        let node = MockNode::start(move |_, params| match round_of(params) {
+            // pruned by the aggregator, looks too recent
+            Some(20..=30) => Err(crate::testing::reverted()),
+            _ => mock.eth_call(params),
+        })
+        .await;
+        let reader = FeedReader::new(&node.url, proxy);
+        let round = reader.round_at_timestamp(1255).await.unwrap();
+        assert_eq!(round.round_id, compose_round_id(3, 19));
+    }
+
+    #[tokio::test]
+    async fn rpc_failures_abort_the_search() {
+        let mock = feed();
+        let proxy = mock.proxy;
+        let node = MockNode::start(move |_, params| match round_of(params) {
+            Some(21) => Err((-32005, "request rate limited".to_string())),
+            _ => mock.eth_call(params),
+        })
+        .await;
+        let reader = FeedReader::new(&node.url, proxy);
+        assert!(matches!(
+            reader.round_at_timestamp(1255).await,
+            Err(Error::Rpc { code: -32005, .. })
+        ));
+
+        // pinned block on a node without the state: first rounds can't be read
+        let mock = feed();
+        let node = MockNode::start(move |_, params| match round_of(params) {
+            Some(_) => Err((-32000, "missing trie node 1a2b".to_string())),
+            None => mock.eth_call(params),
+        })
+        .await;
+        let reader = FeedReader::new(&node.url, proxy).at(1);
+        assert!(matches!(
+            reader.round_at_timestamp(1255).await,
+            Err(Error::Rpc { code: -32000, .. })
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+// Enumerated values of the reference-data-directory
+// every enum keeps unseen values in `Unknown` so new directory entries still load
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Declares a string backed enum, matched case-insensitively
+/// first literal is the canonical form, the rest are accepted aliases
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($variant:ident => $value:literal $(| $alias:literal)*),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum $name {
+            $($variant,)*
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::Unknown(s) => s,
+                }
+            }
+
+            pub fn is_unknown(&self) -> bool {
+                matches!(self, Self::Unknown(_))
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(s: &str) -> Self {
+                let s = s.trim();
+                $(
+                    if s.eq_ignore_ascii_case($value) $(|| s.eq_ignore_ascii_case($alias))* {
+                        return Self::$variant;
+                    }
+                )*
+                Self::Unknown(s.to_string())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = std::convert::Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(Self::from(s))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                Ok(Self::from(s.as_str()))
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Risk tier of a feed (see data.chain.link "feed category")
+    FeedCategory {
+        Low => "low",
+        Medium => "medium",
+        High => "high",
+        Custom => "custom",
+        NewToken => "new_token" | "new token",
+        Deprecating => "deprecating",
+        Verified => "verified",
+        Monitored => "monitored",
+        Provisional => "provisional",
+        Specialized => "specialized",
+    }
+}
+
+string_enum! {
+    /// Kind of value answered by the aggregator
+    ContractType {
+        Numerical => "numerical",
+        Timestamp => "timestamp",
+    }
+}
+
+string_enum! {
+    /// What the feed tracks
+    FeedType {
+        Crypto => "Crypto",
+        CryptoDex => "Crypto-DEX",
+        Forex => "Forex",
+        Fiat => "Fiat",
+        Equities => "Equities",
+        Commodities => "Commodities",
+        Indexes => "Indexes" | "Index",
+        Rates => "Rates",
+        ProofOfReserve => "Proof of Reserve",
+        NftFloorPricing => "NFT Floor Pricing",
+        ExchangeRate => "Exchange Rate",
+    }
+}
+
+string_enum! {
+    /// Trading schedule the feed follows
+    MarketHours {
+        Crypto => "Crypto",
+        Forex => "Forex",
+        UsEquities => "US_Equities",
+        UkEtf => "UK_ETF",
+        Metals => "Metals",
+        Wti => "WTI",
+        Natgas => "Natgas",
+    }
+}
+
+impl FeedCategory {
+    /// Categories data.chain.link flags as risky to consume
+    pub fn is_elevated_risk(&self) -> bool {
+        matches!(self, Self::High | Self::NewToken | Self::Deprecating | Self::Custom)
+    }
+}
+
+impl MarketHours {
+    /// Feed updates around the clock
+    pub fn is_always_open(&self) -> bool {
+        matches!(self, Self::Crypto)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_and_unknown() {
+        assert_eq!(FeedCategory::from(" New Token "), FeedCategory::NewToken);
+        assert_eq!(FeedCategory::from("new_token").as_str(), "new_token");
+        assert_eq!(FeedType::from("index"), FeedType::Indexes);
+        assert_eq!(FeedType::from("proof of reserve"), FeedType::ProofOfReserve);
+        assert_eq!(MarketHours::from("us_equities"), MarketHours::UsEquities);
+
+        let sponsored = FeedCategory::from("sponsored");
+        assert_eq!(sponsored, FeedCategory::Unknown("sponsored".to_string()));
+        assert!(sponsored.is_unknown());
+        assert!(!sponsored.is_elevated_risk());
+        assert_eq!(sponsored.to_string(), "sponsored");
+        assert!(!FeedCategory::Low.is_unknown());
+    }
+
+    #[test]
+    fn serde() {
+        let kinds: Vec<FeedType> =
+            serde_json::from_str(r#"["Crypto", "crypto-dex", "Tokenized Equity"]"#).unwrap();
+        assert_eq!(
+            kinds,
+            [
+                FeedType::Crypto,
+                FeedType::CryptoDex,
+                FeedType::Unknown("Tokenized Equity".to_string())
+            ]
+        );
+        // canonical form out, unknown values as read
+        assert_eq!(
+            serde_json::to_string(&kinds).unwrap(),
+            r#"["Crypto","Crypto-DEX","Tokenized Equity"]"#
+        );
+        assert!(serde_json::from_str::<ContractType>("1").is_err());
+    }
+}
@@ -1,81 +1,124 @@
+pub mod breaker;
+pub mod cache;
 pub mod contracts;
+pub mod cross;
+mod de;
+pub mod docs;
+pub mod error;
+pub mod filter;
+pub mod health;
+pub mod history;
+pub mod kinds;
+pub mod logs;
+pub mod multicall;
+pub mod phases;
+pub mod price;
+pub mod proposal;
+pub mod query;
+pub mod reader;
+pub mod reorg;
+pub mod rounds;
+pub mod rpc;
+pub mod search;
+pub mod source;
+pub mod subscribe;
+#[cfg(test)]
+mod testing;
+pub mod upgrades;
+pub mod validate;
+
+pub use breaker::{AnswerBounds, BoundStatus};
+pub use cache::FeedsCache;
+pub use cross::{CrossRate, CrossRates};
+pub use docs::Docs;
+pub use error::{Error, Result};
+pub use filter::FeedFilter;
+pub use health::{Freshness, StalenessPolicy};
+pub use kinds::{ContractType, FeedCategory, FeedType, MarketHours};
+pub use logs::{FeedEvent, FeedLog, LogScanner};
+pub use multicall::{BatchReader, FeedState};
+pub use phases::Phase;
+pub use price::Price;
+pub use proposal::ProposalReport;
+pub use query::PairMatch;
+pub use reader::{FeedReader, RoundData};
+pub use reorg::{EventFollower, LogCursor, ReorgEvent};
+pub use rounds::{Direction, RoundBound, RoundRange};
+pub use rpc::{BlockTag, RpcClient};
+pub use search::SearchMatch;
+pub use source::ReferenceSource;
+pub use subscribe::{FeedUpdate, Subscription};
+pub use upgrades::{UpgradeEvent, UpgradeWatcher};
+pub use validate::{RoundIssue, RoundPolicy};
 
 use alloy_chains::{Chain, NamedChain};
-use alloy_primitives::Address;
+use alloy_primitives::{Address, U256};
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use std::{
+    io::Read,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// References
 /// taken from https://reference-data-directory.vercel.app
-pub fn get_references_url(chain: NamedChain) -> Option<String> {
-    let base = "https://reference-data-directory.vercel.app";
-    let url = match chain {
-        NamedChain::Mainnet => format!("{}/feeds-mainnet.json",base),
-        NamedChain::Sepolia => format!("{}/feeds-ethereum-testnet-sepolia.json",base),
-        NamedChain::Goerli => format!("{}/feeds-goerli.json",base),
-        NamedChain::BinanceSmartChain => format!("{}/feeds-bsc-mainnet.json",base),
-        NamedChain::BinanceSmartChainTestnet => format!("{}/feeds-bsc-testnet.json",base),
-        NamedChain::Polygon => format!("{}/feeds-matic-mainnet.json",base),
-        NamedChain::PolygonMumbai => format!("{}/feeds-matic-testnet.json",base), 
-        NamedChain::Gnosis => format!("{}/feeds-xdai-mainnet.json",base),
-        NamedChain::Avalanche => format!("{}/feeds-avalanche-mainnet.json",base),
-        NamedChain::AvalancheFuji => format!("{}/feeds-avalanche-fuji-testnet.json",base),
-        NamedChain::Fantom => format!("{}/feeds-fantom-mainnet.json",base),
-        NamedChain::FantomTestnet => format!("{}/feeds-fantom-testnet.json",base),
-        NamedChain::Arbitrum => format!("{}/feeds-ethereum-mainnet-arbitrum-1.json",base),
-        NamedChain::ArbitrumSepolia => format!("{}/feeds-ethereum-testnet-sepolia-arbitrum-1.json",base),
-        NamedChain::Optimism => format!("{}/feeds-ethereum-mainnet-optimism-1.json",base),
-        NamedChain::OptimismSepolia => format!("{}/feeds-ethereum-testnet-sepolia-optimism-1.json",base),
-        NamedChain::OptimismGoerli => format!("{}/feeds-ethereum-testnet-goerli-optimism-1.json",base),
-        NamedChain::Moonriver => format!("{}/feeds-kusama-mainnet-moonriver.json",base),
-        NamedChain::Moonbeam => format!("{}/feeds-polkadot-mainnet-moonbeam.json",base),
-        NamedChain::Metis => format!("{}/feeds-ethereum-mainnet-andromeda-1.json",base),
-        NamedChain::Base => format!("{}/feeds-ethereum-mainnet-base-1.json",base),
-        NamedChain::BaseGoerli => format!("{}/feeds-ethereum-testnet-goerli-base-1.json",base),
-        NamedChain::Celo => format!("{}/feeds-celo-mainnet.json",base),
-        NamedChain::CeloAlfajores => format!("{}/feeds-celo-testnet-alfajores.json",base),
-        NamedChain::Scroll => format!("{}/feeds-ethereum-mainnet-scroll-1.json",base),
-        NamedChain::ScrollSepolia => format!("{}/feeds-ethereum-testnet-sepolia-scroll-1.json",base),
-        NamedChain::Linea => format!("{}/feeds-ethereum-mainnet-linea-1.json",base),
-        NamedChain::LineaTestnet => format!("{}/feeds-ethereum-testnet-goerli-linea-1.json", base),
-        NamedChain::ZkSync => "https://reference-data-directory-qy7u5hvya-chainlinklabs.vercel.app/feeds-ethereum-mainnet-zksync-1.json".to_string(),
-        NamedChain::ZkSyncTestnet => "https://reference-data-directory-qy7u5hvya-chainlinklabs.vercel.app/feeds-ethereum-testnet-goerli-zksync-1.json".to_string(),
-        NamedChain::PolygonZkEvm => "https://reference-data-directory-qy7u5hvya-chainlinklabs.vercel.app/feeds-ethereum-mainnet-polygon-zkevm-1.json".to_string(),
-        NamedChain::PolygonZkEvmTestnet => "https://reference-data-directory-qy7u5hvya-chainlinklabs.vercel.app/feeds-ethereum-testnet-goerli-polygon-zkevm-1.json".to_string(),            
-        _ => panic!("Network not supported")
-    };
-    Some(url)
+pub fn get_references_url(chain: NamedChain) -> Result<String> {
+    ReferenceSource::default().url(chain)
 }
 
 /// Chainlink reference-data-directory model
-/// many fields are actually enums
+/// enum-like fields fall back to `Unknown` for values not modelled yet
 /// check numeric values if are ok (i just guess'em)
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Oracle {
     pub compare_offchain: Option<String>,
     pub contract_address: Option<Address>,
-    pub contract_type: Option<String>,
+    pub contract_type: Option<ContractType>,
     pub contract_version: Option<u32>,
     pub decimal_places: Option<u32>,
     pub ens: Option<String>,
     pub format_decimal_places: Option<u32>,
-    pub health_price: Option<String>,
+    #[serde(default, deserialize_with = "de::decimal_opt")]
+    pub health_price: Option<Decimal>,
     pub heartbeat: Option<u32>,
     pub history: Option<bool>,
-    pub multiply: Option<String>,
+    #[serde(default, deserialize_with = "de::u256_opt")]
+    pub multiply: Option<U256>,
     pub name: Option<String>,
     pub pair: Vec<String>,
     pub path: Option<String>,
     pub proxy_address: Option<Address>,
-    pub threshold: Option<f64>,
+    /// deviation threshold in percent (0.5 = 0.5%)
+    #[serde(default, deserialize_with = "de::decimal_opt")]
+    pub threshold: Option<Decimal>,
     pub value_prefix: Option<String>,
     pub asset_name: Option<String>,
-    pub feed_category: Option<String>,
-    pub feed_type: Option<String>,
-    pub docs: serde_json::Value,
+    pub feed_category: Option<FeedCategory>,
+    pub feed_type: Option<FeedType>,
+    #[serde(default, deserialize_with = "docs::null_as_default")]
+    pub docs: Docs,
     pub decimals: Option<u8>
 }
 
+impl Oracle {
+    /// Deviation threshold in basis points (0.5% = 50)
+    pub fn deviation_threshold_bps(&self) -> Option<Decimal> {
+        self.threshold.map(|t| t * Decimal::ONE_HUNDRED)
+    }
+
+    /// Deviation threshold as a fraction (0.5% = 0.005)
+    pub fn deviation_threshold(&self) -> Option<Decimal> {
+        self.threshold.map(|t| t / Decimal::ONE_HUNDRED)
+    }
+
+    /// Scaling factor applied to the answer, 1 when the directory has none
+    pub fn multiplier(&self) -> U256 {
+        self.multiply.unwrap_or(U256::from(1))
+    }
+}
+
 /// Collection data feeds oracles for a chain
 // Different types of feeds are mixed in this index (see feed_category)
 #[derive(Debug)]
@@ -85,54 +128,150 @@ pub struct OraclesIndex {
 }
 
 impl OraclesIndex {
-    /// Returns a struct loaded with chain specific oracles    
-    pub async fn load_reference_feeds(chain: Chain) -> Self {
-        let url = get_references_url(chain.named().unwrap()).expect("Error getting url of feeds");
-        //println!("Populating information with {url}");
-        let body = reqwest::get(url)
-            .await.expect("Cannot request reference index")
-            .json::<serde_json::Value>()
-            .await.expect("Could not retrieve reference index");
-        let res: Vec<Oracle> = serde_json::from_value(body).expect("Deserialization is not possible"); 
-        Self {
-            chain: chain,
-            feeds: res
-        }
+    /// Returns a struct loaded with chain specific oracles
+    pub async fn load_reference_feeds(chain: Chain) -> Result<Self> {
+        Self::load_from(&ReferenceSource::default(), chain).await
     }
 
-    pub fn print_all_references(&self) -> () {
+    /// Same as `load_reference_feeds` but fetching from a custom source (mirror, test server..)
+    pub async fn load_from(source: &ReferenceSource, chain: Chain) -> Result<Self> {
+        let body = source.fetch(chain).await?;
+        Self::from_json_str(chain, &body)
+    }
+
+    /// Builds the index from a feeds json already in memory
+    pub fn from_json_str(chain: Chain, json: &str) -> Result<Self> {
+        let feeds: Vec<Oracle> = error::decode(json)?;
+        Ok(Self { chain, feeds })
+    }
+
+    /// Builds the index from raw bytes of a feeds json
+    pub fn from_slice(chain: Chain, json: &[u8]) -> Result<Self> {
+        Self::from_reader(chain, json)
+    }
+
+    /// Builds the index from any reader yielding a feeds json
+    pub fn from_reader<R: Read>(chain: Chain, rdr: R) -> Result<Self> {
+        let feeds: Vec<Oracle> = error::decode_reader(rdr)?;
+        Ok(Self { chain, feeds })
+    }
+
+    /// Builds the index from a feeds json snapshot on disk (ie: a pinned copy for offline use)
+    pub fn from_path<P: AsRef<Path>>(chain: Chain, path: P) -> Result<Self> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(chain, std::io::BufReader::new(file))
+    }
+
+    pub fn print_all_references(&self) {
         println!("{:#?}", self.feeds)
     }
     
-    /// Returns the oracle in storage if existent
+    /// Returns the oracle in storage if existent (exact "TOKEN / BASE" name)
+    /// see `find_pair`, `lookup` and the `by_*` methods for looser queries
     pub fn get_oracle(&self, token: &str, base: &str) -> Option<&Oracle> {
         let index = format!("{} / {}", token, base);
         self.feeds
             .iter()
-            .find(|r| r.name.clone().unwrap_or("none".to_string()) == index)
+            .find(|r| r.name.as_deref() == Some(index.as_str()))
     }
 }
 
- /* 
-not used bc of the dyn nature of it.. currently using serde_json::Value
-/// Chainlink reference-data-directory docs
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "snake_case")]
-pub struct DataFeedIndexDocs {
-    asset_class: Option<String>,//"Fiat",
-    asset_name: Option<String>,//"Japanese Yen",
-    base_asset: Option<String>,//"JPY",
-    base_asset_clic: Option<String>,//"JPY_FX",
-    blockchain_name: Option<String>,//"Ethereum",
-    clic_product_name: Option<String>,//"JPY/USD-RefPrice-DF-Ethereum-001",
-    delivery_channel_code: Option<String>,//"DF",
-    feed_category: Option<String>,//"verified",
-    feed_type: Option<String>,//"Forex",
-    market_hours: Option<String>,//"Forex",
-    product_sub_type: Option<String>,//"Reference",
-    product_type: Option<String>,//"Price",
-    product_typeCode: Option<String>,//"RefPrice",
-    quote_asset: Option<String>,//"USD",
-    quote_asset_clic: Option<String>,//"USD_FX"
-} 
-*/
\ No newline at end of file
+/// Current unix time in seconds
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Writes through a temp file renamed over `path`, readers never see half a file
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = std::path::PathBuf::from(tmp);
+    std::fs::write(&tmp, contents)?;
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/feeds-mainnet.json"
+    );
+
+    fn check(index: &OraclesIndex) {
+        assert_eq!(index.chain, Chain::mainnet());
+        assert_eq!(index.feeds.len(), 3);
+        let eth = index.get_oracle("ETH", "USD").unwrap();
+        assert_eq!(eth.multiplier(), U256::from(100_000_000u64));
+        assert_eq!(eth.deviation_threshold_bps(), Some(Decimal::new(50, 0)));
+        assert_eq!(eth.health_price, None);
+        assert_eq!(eth.docs.market_hours, Some(MarketHours::Crypto));
+        assert!(index.get_oracle("JPY", "USD").unwrap().docs.is_deprecated());
+        let por = &index.feeds[2];
+        assert_eq!(por.feed_type, Some(FeedType::ProofOfReserve));
+        assert!(por.feed_category.as_ref().unwrap().is_unknown());
+        assert_eq!(por.docs, Docs::default());
+        assert_eq!(por.contract_address, None);
+    }
+
+    #[test]
+    fn constructors() {
+        let json = std::fs::read_to_string(FIXTURE).unwrap();
+        check(&OraclesIndex::from_json_str(Chain::mainnet(), &json).unwrap());
+        check(&OraclesIndex::from_slice(Chain::mainnet(), json.as_bytes()).unwrap());
+        check(
+            &OraclesIndex::from_reader(Chain::mainnet(), std::fs::File::open(FIXTURE).unwrap())
+                .unwrap(),
+        );
+        check(&OraclesIndex::from_path(Chain::mainnet(), FIXTURE).unwrap());
+        assert!(matches!(
+            OraclesIndex::from_path(Chain::mainnet(), "tests/fixtures/missing.json"),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn decode_errors() {
+        let json = std::fs::read_to_string(FIXTURE)
+            .unwrap()
+            .replace("\"heartbeat\": 86400", "\"heartbeat\": \"daily\"");
+        let path = |e: Result<OraclesIndex>| match e {
+            Err(Error::Decode { path, .. }) => path,
+            other => panic!("expected a decode error, got {other:?}"),
+        };
+        assert_eq!(
+            path(OraclesIndex::from_json_str(Chain::mainnet(), &json)),
+            "[1].heartbeat"
+        );
+        assert_eq!(
+            path(OraclesIndex::from_slice(Chain::mainnet(), json.as_bytes())),
+            "[1].heartbeat"
+        );
+
+        let json = r#"[{"name": "ETH / USD", "proxyAddress": "0x5f4e"}]"#;
+        assert_eq!(
+            path(OraclesIndex::from_json_str(Chain::mainnet(), json)),
+            "[0].proxyAddress"
+        );
+        // `pair` is the only required field
+        assert_eq!(
+            path(OraclesIndex::from_json_str(Chain::mainnet(), r#"[{}]"#)),
+            "[0]"
+        );
+        assert_eq!(
+            path(OraclesIndex::from_json_str(
+                Chain::mainnet(),
+                r#"{"feeds": []}"#
+            )),
+            "."
+        );
+    }
+}
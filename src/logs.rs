@@ -0,0 +1,400 @@
+use crate::{
+    contracts::{
+        AccessControlledAggregator::AccessControlledAggregator,
+        AggregatorContract::AggregatorContract,
+    },
+    de,
+    phases::{compose_round_id, Phase},
+    rpc::{BlockTag, RpcClient},
+    Error, FeedReader, Result,
+};
+use alloy_primitives::{Address, Bytes, B256, I256};
+use alloy_sol_types::SolEvent;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+
+/// Blocks per `eth_getLogs` request, most providers cap ranges around 2k-10k
+pub const DEFAULT_CHUNK_SIZE: u64 = 2_000;
+/// Block headers fetched per JSON-RPC batch for timestamps, batches are capped too
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// `AnswerUpdated` / `NewRound` of an aggregator, round ids are aggregator rounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedEvent {
+    AnswerUpdated {
+        current: I256,
+        round_id: u64,
+        updated_at: u64,
+    },
+    NewRound {
+        round_id: u64,
+        started_by: Address,
+        /// legacy aggregators don't log it
+        started_at: Option<u64>,
+    },
+}
+
+impl FeedEvent {
+    pub fn round_id(&self) -> u64 {
+        match self {
+            FeedEvent::AnswerUpdated { round_id, .. } | FeedEvent::NewRound { round_id, .. } => {
+                *round_id
+            }
+        }
+    }
+
+    /// topic0 of every event decoded (legacy `NewRound` has its own signature)
+    pub fn topics() -> [B256; 3] {
+        [
+            AccessControlledAggregator::AnswerUpdated::SIGNATURE_HASH,
+            AccessControlledAggregator::NewRound::SIGNATURE_HASH,
+            AggregatorContract::NewRound::SIGNATURE_HASH,
+        ]
+    }
+}
+
+/// Decoded log with where and when it was emitted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedLog {
+    pub aggregator: Address,
+    /// proxy phase of the aggregator, when known
+    pub phase: Option<u16>,
+    pub block_number: u64,
+    pub block_hash: B256,
+    pub transaction_hash: B256,
+    pub log_index: u64,
+    /// set by the node when the log was dropped by a reorg
+    pub removed: bool,
+    /// unix seconds of the block
+    pub timestamp: u64,
+    pub event: FeedEvent,
+}
+
+impl FeedLog {
+    /// Round id as seen through the proxy (`getRoundData`)
+    pub fn proxy_round_id(&self) -> Option<u128> {
+        self.phase
+            .map(|phase| compose_round_id(phase, self.event.round_id()))
+    }
+}
+
+/// Log as returned by `eth_getLogs` / `eth_subscribe`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RawLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    #[serde(deserialize_with = "de::quantity")]
+    pub block_number: u64,
+    pub block_hash: B256,
+    pub transaction_hash: B256,
+    #[serde(deserialize_with = "de::quantity")]
+    pub log_index: u64,
+    #[serde(default)]
+    pub removed: bool,
+}
+
+impl RawLog {
+    /// None for events other than `AnswerUpdated` / `NewRound`
+    pub(crate) fn decode(&self) -> Result<Option<FeedEvent>> {
+        let Some(topic0) = self.topics.first() else {
+            return Ok(None);
+        };
+        let topics = self.topics.iter().copied();
+        let event = if *topic0 == AccessControlledAggregator::AnswerUpdated::SIGNATURE_HASH {
+            let e = AccessControlledAggregator::AnswerUpdated::decode_raw_log(
+                topics, &self.data, true,
+            )?;
+            FeedEvent::AnswerUpdated {
+                current: e.current,
+                round_id: e.roundId.saturating_to(),
+                updated_at: e.updatedAt.saturating_to(),
+            }
+        } else if *topic0 == AccessControlledAggregator::NewRound::SIGNATURE_HASH {
+            let e = AccessControlledAggregator::NewRound::decode_raw_log(topics, &self.data, true)?;
+            FeedEvent::NewRound {
+                round_id: e.roundId.saturating_to(),
+                started_by: e.startedBy,
+                started_at: Some(e.startedAt.saturating_to()),
+            }
+        } else if *topic0 == AggregatorContract::NewRound::SIGNATURE_HASH {
+            let e = AggregatorContract::NewRound::decode_raw_log(topics, &self.data, true)?;
+            FeedEvent::NewRound {
+                round_id: e.roundId.saturating_to(),
+                started_by: e.startedBy,
+                started_at: None,
+            }
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(event))
+    }
+}
+
+/// Fetches and decodes `AnswerUpdated` / `NewRound` logs of aggregators
+/// (proxies don't emit them, the aggregators behind do)
+#[derive(Debug, Clone)]
+pub struct LogScanner {
+    pub rpc: RpcClient,
+    pub aggregators: Vec<Address>,
+    /// phase of each aggregator, to map rounds to proxy round ids
+    pub phases: HashMap<Address, u16>,
+    pub chunk_size: u64,
+    pub batch_size: usize,
+}
+
+impl FeedReader {
+    /// Scanner over the aggregators of every phase of the proxy
+    pub async fn log_scanner(&self) -> Result<LogScanner> {
+        let phases = self.phases().await?;
+        Ok(LogScanner::for_phases(self.rpc.clone(), &phases))
+    }
+}
+
+impl LogScanner {
+    pub fn new(rpc: RpcClient, aggregators: Vec<Address>) -> Self {
+        Self {
+            rpc,
+            aggregators,
+            phases: HashMap::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub fn for_phases(rpc: RpcClient, phases: &[Phase]) -> Self {
+        let mut scanner = Self::new(rpc, Vec::new());
+        scanner.set_phases(phases);
+        scanner
+    }
+
+    /// Scans the aggregators of `phases` from now on
+    pub fn set_phases(&mut self, phases: &[Phase]) {
+        self.aggregators = phases.iter().map(|p| p.aggregator).collect();
+        self.phases = phases.iter().map(|p| (p.aggregator, p.id)).collect();
+    }
+
+    /// Blocks per request
+    pub fn chunk_size(mut self, blocks: u64) -> Self {
+        self.chunk_size = blocks.max(1);
+        self
+    }
+
+    /// Block headers per timestamp batch
+    pub fn batch_size(mut self, blocks: usize) -> Self {
+        self.batch_size = blocks.max(1);
+        self
+    }
+
+    /// Decoded logs of `from_block..=to_block`, in chain order
+    pub async fn scan(&self, from_block: u64, to_block: u64) -> Result<Vec<FeedLog>> {
+        let logs = self.raw_logs(from_block, to_block).await?;
+        self.resolve(logs).await
+    }
+
+    /// Splits the range in chunks and halves a chunk again when the provider refuses it
+    /// (too many results, range too large..), other failures are returned
+    pub(crate) async fn raw_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<RawLog>> {
+        if self.aggregators.is_empty() || from_block > to_block {
+            return Ok(Vec::new());
+        }
+        let step = self.chunk_size.max(1);
+        let mut chunks = Vec::new();
+        let mut start = from_block;
+        loop {
+            let end = to_block.min(start.saturating_add(step - 1));
+            chunks.push((start, end));
+            if end == to_block {
+                break;
+            }
+            start = end + 1;
+        }
+        // stack of ranges left, earliest on top
+        chunks.reverse();
+        let mut logs = Vec::new();
+        while let Some((from, to)) = chunks.pop() {
+            match self.get_logs(from, to).await {
+                Ok(found) => logs.extend(found),
+                Err(e) if from < to && is_range_error(&e) => {
+                    let mid = from + (to - from) / 2;
+                    chunks.push((mid + 1, to));
+                    chunks.push((from, mid));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(logs)
+    }
+
+    async fn get_logs(&self, from_block: u64, to_block: u64) -> Result<Vec<RawLog>> {
+        let filter = json!({
+            "fromBlock": BlockTag::Number(from_block).to_param(),
+            "toBlock": BlockTag::Number(to_block).to_param(),
+            "address": self.aggregators,
+            "topics": [FeedEvent::topics()],
+        });
+        let logs: Vec<Value> = self.rpc.request("eth_getLogs", json!([filter])).await?;
+        logs.into_iter()
+            .map(|log| {
+                serde_json::from_value(log).map_err(|source| Error::Decode {
+                    path: "eth_getLogs".to_string(),
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// Decodes the logs and looks up their block timestamps (`batch_size` blocks per batch)
+    pub(crate) async fn resolve(&self, logs: Vec<RawLog>) -> Result<Vec<FeedLog>> {
+        let blocks: Vec<u64> = logs
+            .iter()
+            .map(|log| log.block_number)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let mut timestamps = HashMap::with_capacity(blocks.len());
+        for chunk in blocks.chunks(self.batch_size.max(1)) {
+            let found = self.rpc.block_timestamps(chunk).await?;
+            timestamps.extend(chunk.iter().copied().zip(found));
+        }
+
+        let mut decoded = Vec::with_capacity(logs.len());
+        for log in logs {
+            let Some(event) = log.decode()? else {
+                continue;
+            };
+            decoded.push(FeedLog {
+                aggregator: log.address,
+                phase: self.phases.get(&log.address).copied(),
+                block_number: log.block_number,
+                block_hash: log.block_hash,
+                transaction_hash: log.transaction_hash,
+                log_index: log.log_index,
+                removed: log.removed,
+                timestamp: timestamps[&log.block_number],
+                event,
+            });
+        }
+        Ok(decoded)
+    }
+}
+
+/// Provider refusing an `eth_getLogs` range as too wide or too heavy, worth retrying on a
+/// smaller one (unlike rate limits, which often share the same code)
+fn is_range_error(error: &Error) -> bool {
+    let Error::Rpc { message, .. } = error else {
+        return false;
+    };
+    let message = message.to_lowercase();
+    [
+        "range",
+        "more than",
+        "too many results",
+        "too many logs",
+        "too large",
+        "response size",
+    ]
+    .iter()
+    .any(|marker| message.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::parse_quantity;
+    use crate::testing::{answer_log, MockNode};
+
+    const AGGREGATOR: Address = Address::repeat_byte(7);
+
+    /// node refusing `eth_getLogs` over more than 500 blocks, one log every 100 blocks
+    async fn node() -> MockNode {
+        MockNode::start(|method, params| match method {
+            "eth_getLogs" => {
+                let from = parse_quantity(params[0]["fromBlock"].as_str().unwrap()).unwrap();
+                let to = parse_quantity(params[0]["toBlock"].as_str().unwrap()).unwrap();
+                if to - from >= 500 {
+                    return Err((-32005, "query returned more than 10000 results".to_string()));
+                }
+                let logs: Vec<_> = (from..=to)
+                    .filter(|n| n % 100 == 0)
+                    .map(|n| answer_log(AGGREGATOR, n / 100, n, B256::with_last_byte(1)))
+                    .collect();
+                Ok(Value::Array(logs))
+            }
+            "eth_getBlockByNumber" => {
+                let n = parse_quantity(params[0].as_str().unwrap()).unwrap();
+                Ok(json!({"timestamp": format!("{:#x}", 10 * n)}))
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn bisects_refused_ranges() {
+        let node = node().await;
+        let scanner = LogScanner::for_phases(
+            RpcClient::new(&node.url),
+            &[Phase {
+                id: 3,
+                aggregator: AGGREGATOR,
+                latest_round: 20,
+            }],
+        )
+        .chunk_size(1_000)
+        .batch_size(4);
+        let logs = scanner.scan(50, 1_049).await.unwrap();
+
+        let rounds: Vec<_> = logs.iter().map(|log| log.event.round_id()).collect();
+        assert_eq!(rounds, (1..=10).collect::<Vec<_>>());
+        assert_eq!(logs[0].timestamp, 1_000);
+        assert_eq!(logs[0].proxy_round_id(), Some(compose_round_id(3, 1)));
+        assert_eq!(
+            logs[0].event,
+            FeedEvent::AnswerUpdated {
+                current: I256::try_from(100).unwrap(),
+                round_id: 1,
+                updated_at: 1_001,
+            }
+        );
+
+        // 10 blocks to timestamp, 4 per batch
+        let batches: Vec<_> = node
+            .requests()
+            .into_iter()
+            .filter_map(|body| body.as_array().map(Vec::len))
+            .collect();
+        assert_eq!(batches, vec![4, 4, 2]);
+    }
+
+    #[tokio::test]
+    async fn other_failures_are_returned() {
+        let node =
+            MockNode::start(|_, _| Err((-32005, "daily request count exceeded".to_string()))).await;
+        let scanner = LogScanner::new(RpcClient::new(&node.url), vec![AGGREGATOR]);
+        assert!(matches!(
+            scanner.scan(0, 10_000).await,
+            Err(Error::Rpc { code: -32005, .. })
+        ));
+        assert_eq!(node.requests().len(), 1);
+    }
+
+    #[test]
+    fn range_errors() {
+        let rpc = |code, message: &str| Error::Rpc {
+            code,
+            message: message.to_string(),
+        };
+        assert!(is_range_error(&rpc(
+            -32005,
+            "query returned more than 10000 results"
+        )));
+        assert!(is_range_error(&rpc(-32600, "block range is too wide")));
+        assert!(is_range_error(&rpc(-32602, "Log response size exceeded.")));
+        assert!(!is_range_error(&rpc(-32005, "request rate limited")));
+        assert!(!is_range_error(&rpc(429, "Too Many Requests")));
+        assert!(!is_range_error(&rpc(-32000, "header not found")));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    contracts::{EACAggregatorProxy::EACAggregatorProxy, Multicall3::Multicall3},
+    rpc::{decode_returns, is_revert, BlockTag, RpcClient},
+    Error, Price, Result, RoundData,
+};
+use alloy_primitives::{address, Address, Bytes};
+use alloy_sol_types::SolCall;
+
+/// Multicall3 deployment shared by most EVM chains
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// Calls packed per `aggregate3` (or JSON-RPC batch), keeps each `eth_call`
+/// well under the usual node gas caps
+pub const DEFAULT_CHUNK_SIZE: usize = 300;
+
+/// Reads of one proxy, each can fail on its own
+#[derive(Debug)]
+pub struct FeedState {
+    pub proxy: Address,
+    pub round: Result<RoundData>,
+    pub decimals: Result<u8>,
+    pub description: Result<String>,
+}
+
+impl FeedState {
+    /// Latest answer when both the round and decimals could be read
+    pub fn price(&self) -> Option<Price> {
+        let (Ok(round), Ok(decimals)) = (&self.round, &self.decimals) else {
+            return None;
+        };
+        Some(Price::from_round(round, *decimals))
+    }
+}
+
+/// Reads many feeds at once through Multicall3 `aggregate3`
+/// chains without Multicall3 (or with `multicall` unset) fall back to JSON-RPC batches
+#[derive(Debug, Clone)]
+pub struct BatchReader {
+    pub rpc: RpcClient,
+    pub block: BlockTag,
+    pub multicall: Option<Address>,
+    pub chunk_size: usize,
+}
+
+impl BatchReader {
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        Self::with_client(RpcClient::new(rpc_url))
+    }
+
+    pub fn with_client(rpc: RpcClient) -> Self {
+        Self {
+            rpc,
+            block: BlockTag::Latest,
+            multicall: Some(MULTICALL3_ADDRESS),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Runs following calls at `block` instead of latest
+    pub fn at(mut self, block: impl Into<BlockTag>) -> Self {
+        self.block = block.into();
+        self
+    }
+
+    /// Multicall3 deployed somewhere else
+    pub fn with_multicall(mut self, multicall: Address) -> Self {
+        self.multicall = Some(multicall);
+        self
+    }
+
+    /// Always use JSON-RPC batches
+    pub fn without_multicall(mut self) -> Self {
+        self.multicall = None;
+        self
+    }
+
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// `latestRoundData`, `decimals` and `description` of every proxy, in the same order
+    pub async fn read_feeds(&self, proxies: &[Address]) -> Result<Vec<FeedState>> {
+        let mut calls = Vec::with_capacity(proxies.len() * 3);
+        for proxy in proxies {
+            calls.push((*proxy, EACAggregatorProxy::latestRoundDataCall {}.abi_encode()));
+            calls.push((*proxy, EACAggregatorProxy::decimalsCall {}.abi_encode()));
+            calls.push((*proxy, EACAggregatorProxy::descriptionCall {}.abi_encode()));
+        }
+        let mut outputs = self.call_raw(&calls).await?.into_iter();
+        let mut feeds = Vec::with_capacity(proxies.len());
+        for proxy in proxies {
+            let (Some(round), Some(decimals), Some(description)) =
+                (outputs.next(), outputs.next(), outputs.next())
+            else {
+                return Err(Error::InvalidResponse("missing batch output".to_string()));
+            };
+            feeds.push(FeedState {
+                proxy: *proxy,
+                round: decode::<EACAggregatorProxy::latestRoundDataCall>(*proxy, round)
+                    .map(Into::into),
+                decimals: decode::<EACAggregatorProxy::decimalsCall>(*proxy, decimals)
+                    .map(|r| r._0),
+                description: decode::<EACAggregatorProxy::descriptionCall>(*proxy, description)
+                    .map(|r| r._0),
+            });
+        }
+        Ok(feeds)
+    }
+
+    /// `latestRoundData` of every proxy, in the same order
+    pub async fn latest_rounds(&self, proxies: &[Address]) -> Result<Vec<Result<RoundData>>> {
+        let calls: Vec<_> = proxies
+            .iter()
+            .map(|proxy| (*proxy, EACAggregatorProxy::latestRoundDataCall {}))
+            .collect();
+        let rounds = self.call_many(&calls).await?;
+        Ok(rounds.into_iter().map(|r| r.map(Into::into)).collect())
+    }
+
+    /// Any calls, one result per call in the same order
+    pub async fn call_many<C: SolCall>(
+        &self,
+        calls: &[(Address, C)],
+    ) -> Result<Vec<Result<C::Return>>> {
+        let raw: Vec<(Address, Vec<u8>)> = calls
+            .iter()
+            .map(|(to, call)| (*to, call.abi_encode()))
+            .collect();
+        let outputs = self.call_raw(&raw).await?;
+        Ok(calls
+            .iter()
+            .zip(outputs)
+            .map(|((to, _), output)| decode::<C>(*to, output))
+            .collect())
+    }
+
+    /// Raw outputs, a failed call is an `Error::Rpc` whichever way it was sent
+    pub async fn call_raw(&self, calls: &[(Address, Vec<u8>)]) -> Result<Vec<Result<Bytes>>> {
+        let mut multicall = self.multicall;
+        let mut outputs = Vec::with_capacity(calls.len());
+        for chunk in calls.chunks(self.chunk_size.max(1)) {
+            let results = match multicall {
+                Some(address) => match self.aggregate3(address, chunk).await {
+                    Ok(results) => results,
+                    // no contract there: empty output (or a revert on some nodes)
+                    Err(e) if is_revert(&e) || matches!(e, Error::EmptyOutput { .. }) => {
+                        multicall = None;
+                        self.rpc.eth_call_batch(chunk, self.block).await?
+                    }
+                    Err(e) => return Err(e),
+                },
+                None => self.rpc.eth_call_batch(chunk, self.block).await?,
+            };
+            outputs.extend(results);
+        }
+        Ok(outputs)
+    }
+
+    async fn aggregate3(
+        &self,
+        multicall: Address,
+        calls: &[(Address, Vec<u8>)],
+    ) -> Result<Vec<Result<Bytes>>> {
+        let call = Multicall3::aggregate3Call {
+            calls: calls
+                .iter()
+                .map(|(target, data)| Multicall3::Call3 {
+                    target: *target,
+                    allowFailure: true,
+                    callData: data.clone(),
+                })
+                .collect(),
+        };
+        let results = self.rpc.call(multicall, &call, self.block).await?.returnData;
+        if results.len() != calls.len() {
+            return Err(Error::InvalidResponse(format!(
+                "aggregate3 returned {} results for {} calls",
+                results.len(),
+                calls.len()
+            )));
+        }
+        Ok(results
+            .into_iter()
+            .map(|r| match r.success {
+                true => Ok(r.returnData.into()),
+                // same shape as a reverted `eth_call` (geth uses code 3)
+                false => Err(Error::Rpc {
+                    code: 3,
+                    message: "execution reverted".to_string(),
+                }),
+            })
+            .collect())
+    }
+}
+
+/// Output of a call to `to`, decoded as the plain rpc path does
+pub(crate) fn decode<C: SolCall>(to: Address, output: Result<Bytes>) -> Result<C::Return> {
+    decode_returns::<C>(to, &output?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{call_args, output, MockFeed, MockNode};
+    use serde_json::{json, Value};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn feed() -> MockFeed {
+        MockFeed {
+            proxy: address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            phases: vec![(Address::repeat_byte(1), vec![100, 200])],
+        }
+    }
+
+    /// Multicall3 running the calls against `feed`
+    fn aggregate3(feed: &MockFeed, params: &Value) -> Value {
+        let (_, data) = call_args(params);
+        let call = Multicall3::aggregate3Call::abi_decode(&data, true).unwrap();
+        let results: Vec<_> = call
+            .calls
+            .iter()
+            .map(|c| {
+                let params = json!([{
+                    "to": c.target.to_string(),
+                    "data": alloy_primitives::hex::encode_prefixed(&c.callData),
+                }]);
+                match feed.eth_call(&params) {
+                    Ok(out) => Multicall3::Result {
+                        success: true,
+                        returnData: alloy_primitives::hex::decode(out.as_str().unwrap()).unwrap(),
+                    },
+                    Err(_) => Multicall3::Result {
+                        success: false,
+                        returnData: Vec::new(),
+                    },
+                }
+            })
+            .collect();
+        output(Multicall3::aggregate3Call::abi_encode_returns(&(results,)))
+    }
+
+    #[tokio::test]
+    async fn multicall_and_fallback() {
+        let proxy = feed().proxy;
+        let calls = [
+            (
+                proxy,
+                EACAggregatorProxy::latestRoundDataCall {}.abi_encode(),
+            ),
+            (proxy, EACAggregatorProxy::descriptionCall {}.abi_encode()),
+        ];
+        let check = |mut outputs: Vec<Result<Bytes>>| {
+            let description = outputs.pop().unwrap();
+            let round = outputs.pop().unwrap();
+            let round = decode::<EACAggregatorProxy::latestRoundDataCall>(proxy, round).unwrap();
+            assert_eq!(round.updatedAt, alloy_primitives::U256::from(200));
+            assert!(matches!(description, Err(Error::Rpc { code: 3, .. })));
+        };
+
+        let mock = feed();
+        let node = MockNode::start(move |_, params| match call_args(params).0 {
+            MULTICALL3_ADDRESS => Ok(aggregate3(&mock, params)),
+            _ => panic!("call sent outside of the multicall"),
+        })
+        .await;
+        check(BatchReader::new(&node.url).call_raw(&calls).await.unwrap());
+
+        // no Multicall3 deployed: empty output, then plain batches
+        let mock = feed();
+        let node = MockNode::start(move |_, params| match call_args(params).0 {
+            MULTICALL3_ADDRESS => Ok(json!("0x")),
+            _ => mock.eth_call(params),
+        })
+        .await;
+        let reader = BatchReader::new(&node.url).chunk_size(1);
+        check(reader.call_raw(&calls).await.unwrap());
+        // the multicall is only tried for the first chunk
+        assert_eq!(node.requests().len(), 3);
+
+        // anything else is not a missing multicall
+        let sent = Arc::new(AtomicUsize::new(0));
+        let counter = sent.clone();
+        let node = MockNode::start(move |_, _| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err((-32005, "request rate limited".to_string()))
+        })
+        .await;
+        assert!(matches!(
+            BatchReader::new(&node.url).call_raw(&calls).await,
+            Err(Error::Rpc { code: -32005, .. })
+        ));
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn empty_outputs() {
+        // no code at `to`: aggregate3 succeeds with nothing, eth_call returns "0x"
+        let node = MockNode::start(|_, params| match call_args(params).0 {
+            MULTICALL3_ADDRESS => {
+                let (_, data) = call_args(params);
+                let call = Multicall3::aggregate3Call::abi_decode(&data, true).unwrap();
+                let results: Vec<_> = call
+                    .calls
+                    .iter()
+                    .map(|_| Multicall3::Result {
+                        success: true,
+                        returnData: Vec::new(),
+                    })
+                    .collect();
+                Ok(output(Multicall3::aggregate3Call::abi_encode_returns(&(
+                    results,
+                ))))
+            }
+            _ => Ok(json!("0x")),
+        })
+        .await;
+        let to = Address::repeat_byte(0x99);
+        let calls = [(to, EACAggregatorProxy::decimalsCall {})];
+        for reader in [
+            BatchReader::new(&node.url),
+            BatchReader::new(&node.url).without_multicall(),
+        ] {
+            let mut outputs = reader.call_many(&calls).await.unwrap();
+            assert!(matches!(outputs.pop(), Some(Err(Error::EmptyOutput { to: t })) if t == to));
+        }
+        let reader = crate::FeedReader::new(&node.url, to);
+        assert!(matches!(reader.decimals().await, Err(Error::EmptyOutput { to: t }) if t == to));
+    }
+}
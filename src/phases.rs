@@ -0,0 +1,258 @@
+use crate::{
+    contracts::{AggregatorContract::AggregatorContract, EACAggregatorProxy::EACAggregatorProxy},
+    rpc::is_revert,
+    FeedReader, Result, RoundData,
+};
+use alloy_primitives::{Address, U256};
+
+/// Proxy round ids are `phaseId << 64 | aggregatorRoundId`
+pub const PHASE_OFFSET: u32 = 64;
+
+/// Splits a proxy round id into (phase, aggregator round)
+pub fn split_round_id(round_id: u128) -> (u16, u64) {
+    ((round_id >> PHASE_OFFSET) as u16, round_id as u64)
+}
+
+/// Proxy round id of `aggregator_round` in `phase`
+pub fn compose_round_id(phase: u16, aggregator_round: u64) -> u128 {
+    (u128::from(phase) << PHASE_OFFSET) | u128::from(aggregator_round)
+}
+
+/// Aggregator that served a phase of the proxy and the rounds it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phase {
+    pub id: u16,
+    pub aggregator: Address,
+    /// latest aggregator round, 0 when the aggregator never answered
+    pub latest_round: u64,
+}
+
+impl Phase {
+    pub fn is_empty(&self) -> bool {
+        self.latest_round == 0
+    }
+
+    /// Proxy round ids of the phase (aggregator rounds start at 1)
+    pub fn first_round_id(&self) -> Option<u128> {
+        (!self.is_empty()).then(|| compose_round_id(self.id, 1))
+    }
+
+    pub fn last_round_id(&self) -> Option<u128> {
+        (!self.is_empty()).then(|| compose_round_id(self.id, self.latest_round))
+    }
+
+    pub fn contains(&self, round_id: u128) -> bool {
+        let (phase, round) = split_round_id(round_id);
+        phase == self.id && round >= 1 && round <= self.latest_round
+    }
+}
+
+impl FeedReader {
+    pub async fn phase_id(&self) -> Result<u16> {
+        let call = EACAggregatorProxy::phaseIdCall {};
+        Ok(self.rpc.call(self.proxy, &call, self.block).await?._0)
+    }
+
+    /// Aggregator of a phase, zero address for unknown phases
+    pub async fn phase_aggregator(&self, phase: u16) -> Result<Address> {
+        let call = EACAggregatorProxy::phaseAggregatorsCall { _0: phase };
+        Ok(self.rpc.call(self.proxy, &call, self.block).await?._0)
+    }
+
+    /// Phase with its round range, None if the proxy has no aggregator for it
+    pub async fn phase(&self, phase: u16) -> Result<Option<Phase>> {
+        let aggregator = self.phase_aggregator(phase).await?;
+        if aggregator == Address::ZERO {
+            return Ok(None);
+        }
+        let call = AggregatorContract::latestRoundCall {};
+        let latest: U256 = self.rpc.call(aggregator, &call, self.block).await?._0;
+        Ok(Some(Phase {
+            id: phase,
+            aggregator,
+            latest_round: latest.saturating_to(),
+        }))
+    }
+
+    /// Every phase of the proxy, oldest first
+    pub async fn phases(&self) -> Result<Vec<Phase>> {
+        let current = self.phase_id().await?;
+        let mut phases = Vec::with_capacity(usize::from(current));
+        for id in 1..=current {
+            if let Some(phase) = self.phase(id).await? {
+                phases.push(phase);
+            }
+        }
+        Ok(phases)
+    }
+
+    /// `getRoundData` through the proxy for any (phase encoded) round id
+    pub async fn get_round_data(&self, round_id: u128) -> Result<RoundData> {
+        let call = EACAggregatorProxy::getRoundDataCall { _roundId: round_id };
+        let round: RoundData = self.rpc.call(self.proxy, &call, self.block).await?.into();
+        self.check(&round)?;
+        Ok(round)
+    }
+
+    /// Like `get_round_data` but None for rounds that don't exist
+    /// (newer aggregators revert, older ones answer zeros)
+    /// any other failure (rate limit, pruned state..) is returned as is
+    pub async fn try_round_data(&self, round_id: u128) -> Result<Option<RoundData>> {
+        let call = EACAggregatorProxy::getRoundDataCall { _roundId: round_id };
+        match self.rpc.call(self.proxy, &call, self.block).await {
+            Ok(r) => {
+                let round: RoundData = r.into();
+                Ok((round.updated_at != 0).then_some(round))
+            }
+            Err(e) if is_revert(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Round id before `round_id`, jumping to the last round of the previous non empty phase
+/// a round past the end of its phase steps back to the last round of that phase
+pub fn previous_round_id(phases: &[Phase], round_id: u128) -> Option<u128> {
+    let (phase, round) = split_round_id(round_id);
+    if round > 1 && phases.iter().any(|p| p.contains(round_id - 1)) {
+        return Some(round_id - 1);
+    }
+    if let Some(current) = phases.iter().find(|p| p.id == phase) {
+        if !current.is_empty() && round > current.latest_round {
+            return current.last_round_id();
+        }
+    }
+    phases
+        .iter()
+        .rev()
+        .filter(|p| p.id < phase)
+        .find_map(Phase::last_round_id)
+}
+
+/// Round id after `round_id`, jumping to the first round of the next non empty phase
+pub fn next_round_id(phases: &[Phase], round_id: u128) -> Option<u128> {
+    let (phase, _) = split_round_id(round_id);
+    if phases.iter().any(|p| p.contains(round_id + 1)) {
+        return Some(round_id + 1);
+    }
+    phases
+        .iter()
+        .filter(|p| p.id > phase)
+        .find_map(Phase::first_round_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{call_args, output, reverted, MockNode};
+    use alloy_primitives::{address, I256};
+    use alloy_sol_types::SolCall;
+
+    #[tokio::test]
+    async fn missing_rounds() {
+        let node = MockNode::start(|_, params| {
+            let (_, data) = call_args(params);
+            let call = EACAggregatorProxy::getRoundDataCall::abi_decode(&data, true).unwrap();
+            let (_, round) = split_round_id(call._roundId);
+            let updated_at = match round {
+                1 => 1_700_000_000u64,
+                2 => return Err(reverted()),
+                3 => 0,
+                4 => return Err((-32005, "daily request count exceeded".to_string())),
+                _ => return Err((-32000, "missing trie node 5f2a".to_string())),
+            };
+            Ok(output(
+                EACAggregatorProxy::getRoundDataCall::abi_encode_returns(&(
+                    call._roundId,
+                    I256::ONE,
+                    U256::from(updated_at),
+                    U256::from(updated_at),
+                    call._roundId,
+                )),
+            ))
+        })
+        .await;
+        let reader = FeedReader::new(
+            &node.url,
+            address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+        );
+        let round = |n| reader.try_round_data(compose_round_id(2, n));
+
+        assert_eq!(round(1).await.unwrap().unwrap().updated_at, 1_700_000_000);
+        assert_eq!(round(2).await.unwrap(), None);
+        assert_eq!(round(3).await.unwrap(), None);
+        assert!(matches!(
+            round(4).await,
+            Err(crate::Error::Rpc { code: -32005, .. })
+        ));
+        assert!(matches!(
+            round(5).await,
+            Err(crate::Error::Rpc { code: -32000, .. })
+        ));
+    }
+
+    #[test]
+    fn round_ids() {
+        let id = compose_round_id(3, 42);
+        assert_eq!(id, 0x3_0000_0000_0000_002a);
+        assert_eq!(split_round_id(id), (3, 42));
+        assert_eq!(
+            split_round_id(compose_round_id(u16::MAX, u64::MAX)),
+            (u16::MAX, u64::MAX)
+        );
+
+        let phase = |id, latest_round| Phase {
+            id,
+            aggregator: Address::repeat_byte(id as u8),
+            latest_round,
+        };
+        // phases 2 and 4 never answered
+        let phases = [
+            phase(1, 3),
+            phase(2, 0),
+            phase(3, 2),
+            phase(4, 0),
+            phase(5, 1),
+        ];
+        assert!(phases[1].is_empty());
+        assert_eq!(phases[1].first_round_id(), None);
+        assert!(phases[0].contains(compose_round_id(1, 3)));
+        assert!(!phases[0].contains(compose_round_id(1, 0)));
+        assert!(!phases[0].contains(compose_round_id(1, 4)));
+
+        let walk = |step: fn(&[Phase], u128) -> Option<u128>, start| {
+            std::iter::successors(Some(start), |id| step(&phases, *id))
+                .map(split_round_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            walk(next_round_id, compose_round_id(1, 1)),
+            [(1, 1), (1, 2), (1, 3), (3, 1), (3, 2), (5, 1)]
+        );
+        assert_eq!(
+            walk(previous_round_id, compose_round_id(5, 1)),
+            [(5, 1), (3, 2), (3, 1), (1, 3), (1, 2), (1, 1)]
+        );
+        // from a round past the phase end or in an unknown phase
+        assert_eq!(
+            next_round_id(&phases, compose_round_id(3, 9)),
+            Some(compose_round_id(5, 1))
+        );
+        assert_eq!(
+            previous_round_id(&phases, compose_round_id(3, 9)),
+            Some(compose_round_id(3, 2))
+        );
+        assert_eq!(
+            previous_round_id(&phases, compose_round_id(1, u64::MAX)),
+            Some(compose_round_id(1, 3))
+        );
+        assert_eq!(
+            previous_round_id(&phases, compose_round_id(9, 1)),
+            Some(compose_round_id(5, 1))
+        );
+        assert_eq!(
+            next_round_id(&phases, compose_round_id(0, 5)),
+            Some(compose_round_id(1, 1))
+        );
+    }
+}
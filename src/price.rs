@@ -0,0 +1,212 @@
+use crate::{Oracle, RoundData};
+use alloy_primitives::{Sign, I256, U256};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// Raw feed answer together with its precision, value = answer / 10^decimals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Price {
+    pub answer: I256,
+    pub decimals: u8,
+}
+
+impl Price {
+    pub fn new(answer: I256, decimals: u8) -> Self {
+        Self { answer, decimals }
+    }
+
+    pub fn from_round(round: &RoundData, decimals: u8) -> Self {
+        Self::new(round.answer, decimals)
+    }
+
+    /// Uses the precision of the directory entry: `multiply`, else `decimals`, else
+    /// `decimal_places`
+    /// None when `multiply` isn't a power of ten, a price only scales by 10^decimals
+    pub fn for_oracle(answer: I256, oracle: &Oracle) -> Option<Self> {
+        let decimals = match oracle.multiply {
+            Some(multiply) => power_of_ten(multiply)?,
+            None => oracle
+                .decimals
+                .or(oracle.decimal_places.and_then(|d| u8::try_from(d).ok()))
+                .unwrap_or_default(),
+        };
+        Some(Self::new(answer, decimals))
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.answer.is_negative()
+    }
+
+    /// Same value with another precision, extra digits are truncated toward zero
+    /// None if the answer doesn't fit in an int256 anymore
+    pub fn rescale(&self, decimals: u8) -> Option<Price> {
+        let (sign, abs) = self.answer.into_sign_and_abs();
+        let abs = if decimals >= self.decimals {
+            abs.checked_mul(exp10(decimals - self.decimals)?)?
+        } else {
+            abs / exp10(self.decimals - decimals)?
+        };
+        Some(Price::new(I256::checked_from_sign_and_abs(sign, abs)?, decimals))
+    }
+
+    /// Like `rescale` but rounding half away from zero when digits are dropped
+    pub fn round(&self, decimals: u8) -> Option<Price> {
+        if decimals >= self.decimals {
+            return self.rescale(decimals);
+        }
+        let (sign, abs) = self.answer.into_sign_and_abs();
+        let divisor = exp10(self.decimals - decimals)?;
+        let mut abs_q = abs / divisor;
+        if (abs % divisor) * U256::from(2) >= divisor {
+            abs_q += U256::from(1);
+        }
+        Some(Price::new(I256::checked_from_sign_and_abs(sign, abs_q)?, decimals))
+    }
+
+    /// Exact decimal value, None when it exceeds rust_decimal range (28 digits)
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        Decimal::from_str_exact(&self.to_string()).ok()
+    }
+
+    /// Value with `places` decimals (rounded), thousands separators and a prefix ("$")
+    pub fn format(&self, places: u8, prefix: &str) -> String {
+        let rounded = self.round(places).unwrap_or(*self);
+        let digits = rounded.to_string();
+        let (sign, digits) = match digits.strip_prefix('-') {
+            Some(d) => ("-", d),
+            None => ("", digits.as_str()),
+        };
+        let (int, frac) = digits.split_once('.').unwrap_or((digits, ""));
+        let mut grouped = String::with_capacity(int.len() + int.len() / 3);
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        if frac.is_empty() {
+            format!("{}{}{}", sign, prefix, grouped)
+        } else {
+            format!("{}{}{}.{}", sign, prefix, grouped, frac)
+        }
+    }
+
+    /// Formats as data.chain.link does, with `format_decimal_places` and `value_prefix`
+    pub fn format_for(&self, oracle: &Oracle) -> String {
+        let places = oracle
+            .format_decimal_places
+            .and_then(|d| u8::try_from(d).ok())
+            .unwrap_or(self.decimals);
+        self.format(places, oracle.value_prefix.as_deref().unwrap_or_default())
+    }
+}
+
+/// Exact value, all `decimals` digits are kept ("3012.45000000")
+impl fmt::Display for Price {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (sign, abs) = self.answer.into_sign_and_abs();
+        let digits = abs.to_string();
+        let decimals = usize::from(self.decimals);
+        let digits = format!("{:0>width$}", digits, width = decimals + 1);
+        let (int, frac) = digits.split_at(digits.len() - decimals);
+        if sign == Sign::Negative && !abs.is_zero() {
+            f.write_str("-")?;
+        }
+        if frac.is_empty() {
+            f.write_str(int)
+        } else {
+            write!(f, "{}.{}", int, frac)
+        }
+    }
+}
+
+fn exp10(n: u8) -> Option<U256> {
+    U256::from(10).checked_pow(U256::from(n))
+}
+
+/// Exponent of `n` if it is 10^k
+fn power_of_ten(n: U256) -> Option<u8> {
+    let mut k = 0u8;
+    let mut n = n;
+    let ten = U256::from(10);
+    while n > U256::from(1) {
+        if n % ten != U256::ZERO {
+            return None;
+        }
+        n /= ten;
+        k += 1;
+    }
+    (n == U256::from(1)).then_some(k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(answer: i64, decimals: u8) -> Price {
+        Price::new(I256::try_from(answer).unwrap(), decimals)
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(price(301245000000, 8).to_string(), "3012.45000000");
+        assert_eq!(price(5, 8).to_string(), "0.00000005");
+        assert_eq!(price(-5, 2).to_string(), "-0.05");
+        assert_eq!(price(42, 0).to_string(), "42");
+        assert_eq!(price(0, 3).to_string(), "0.000");
+        assert_eq!(
+            price(-123456, 4).to_decimal(),
+            Some(Decimal::new(-123456, 4))
+        );
+        assert_eq!(Price::new(I256::MAX, 0).to_decimal(), None);
+    }
+
+    #[test]
+    fn rescale_and_round() {
+        assert_eq!(price(123456, 4).rescale(6), Some(price(12345600, 6)));
+        // truncated toward zero
+        assert_eq!(price(123456, 4).rescale(2), Some(price(1234, 2)));
+        assert_eq!(price(-123456, 4).rescale(2), Some(price(-1234, 2)));
+        assert_eq!(Price::new(I256::MAX, 0).rescale(1), None);
+        // half away from zero
+        assert_eq!(price(12345, 3).round(2), Some(price(1235, 2)));
+        assert_eq!(price(-12345, 3).round(2), Some(price(-1235, 2)));
+        assert_eq!(price(12344, 3).round(2), Some(price(1234, 2)));
+        assert_eq!(price(-5, 1).round(0), Some(price(-1, 0)));
+        assert_eq!(price(12, 1).round(3), Some(price(1200, 3)));
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(price(123456789012, 4).format(2, "$"), "$12,345,678.90");
+        assert_eq!(price(-99999, 2).format(0, "$"), "-$1,000");
+        assert_eq!(price(999, 3).format(0, ""), "1");
+        assert_eq!(price(123, 0).format(2, "€"), "€123.00");
+
+        let oracle: Oracle = serde_json::from_str(
+            r#"{"pair": ["ETH", "USD"], "multiply": "100000000", "decimals": 18,
+                "formatDecimalPlaces": 2, "valuePrefix": "$"}"#,
+        )
+        .unwrap();
+        let eth = Price::for_oracle(I256::try_from(301245678901i64).unwrap(), &oracle).unwrap();
+        assert_eq!(eth.decimals, 8);
+        assert_eq!(eth.format_for(&oracle), "$3,012.46");
+
+        // multiply that is not a power of ten can't be expressed as decimals
+        let oracle: Oracle = serde_json::from_str(
+            r#"{"pair": ["X", "Y"], "multiply": "12345", "decimalPlaces": 6}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Price::for_oracle(I256::try_from(1234567).unwrap(), &oracle),
+            None
+        );
+
+        // without multiply: decimals, then decimal places
+        let oracle: Oracle =
+            serde_json::from_str(r#"{"pair": ["X", "Y"], "decimalPlaces": 6}"#).unwrap();
+        let x = Price::for_oracle(I256::try_from(1234567).unwrap(), &oracle).unwrap();
+        assert_eq!(x.decimals, 6);
+        assert_eq!(x.format_for(&oracle), "1.234567");
+    }
+}
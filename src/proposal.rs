@@ -0,0 +1,223 @@
+use crate::{
+    contracts::EACAggregatorProxy::EACAggregatorProxy,
+    rpc::{reverted_as_none, BlockTag},
+    FeedReader, Price, Result, RoundData,
+};
+use alloy_primitives::Address;
+use rust_decimal::Decimal;
+
+/// Latest round of a proposed aggregator next to the live one, read at the same block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProposalReport {
+    /// aggregator the proxy reads from now
+    pub aggregator: Address,
+    pub proposed: Address,
+    pub block: BlockTag,
+    pub live: RoundData,
+    /// round ids are the aggregator ones, not phase encoded
+    /// None while the proposed aggregator has no round (`proposedLatestRoundData` reverts)
+    pub proposed_round: Option<RoundData>,
+    pub live_decimals: u8,
+    /// None when the proposed aggregator doesn't expose `decimals`
+    pub proposed_decimals: Option<u8>,
+    pub decimals_match: bool,
+    /// proposed - live, at the larger precision of both
+    /// None without a proposed round, proposed decimals or on overflow
+    pub price_delta: Option<Price>,
+    /// `price_delta` relative to the live answer, None if that is zero
+    pub deviation_bps: Option<Decimal>,
+    /// proposed - live `updatedAt` (seconds), negative when the proposed one is behind
+    /// None without a proposed round
+    pub timestamp_delta: Option<i64>,
+}
+
+impl ProposalReport {
+    pub fn live_price(&self) -> Price {
+        Price::from_round(&self.live, self.live_decimals)
+    }
+
+    pub fn proposed_price(&self) -> Option<Price> {
+        let round = self.proposed_round.as_ref()?;
+        self.proposed_decimals.map(|decimals| Price::from_round(round, decimals))
+    }
+}
+
+impl FeedReader {
+    /// Aggregator waiting for `confirmAggregator`, if any
+    pub async fn proposed_aggregator(&self) -> Result<Option<Address>> {
+        let call = EACAggregatorProxy::proposedAggregatorCall {};
+        let proposed = self.rpc.call(self.proxy, &call, self.block).await?._0;
+        Ok((proposed != Address::ZERO).then_some(proposed))
+    }
+
+    /// `latestRoundData` of the proposed aggregator (reverts when there is none)
+    pub async fn proposed_latest_round_data(&self) -> Result<RoundData> {
+        let call = EACAggregatorProxy::proposedLatestRoundDataCall {};
+        let round: RoundData = self.rpc.call(self.proxy, &call, self.block).await?.into();
+        self.check(&round)?;
+        Ok(round)
+    }
+
+    /// `getRoundData` of the proposed aggregator, `round_id` is an aggregator round id
+    pub async fn proposed_round_data(&self, round_id: u128) -> Result<RoundData> {
+        let call = EACAggregatorProxy::proposedGetRoundDataCall { _roundId: round_id };
+        let round: RoundData = self.rpc.call(self.proxy, &call, self.block).await?.into();
+        self.check(&round)?;
+        Ok(round)
+    }
+
+    /// Compares the proposed aggregator with the live one, None when nothing is proposed
+    /// rounds are reported as read, the reader policy is not applied
+    pub async fn proposal_report(&self) -> Result<Option<ProposalReport>> {
+        let block = match self.block {
+            BlockTag::Number(n) => BlockTag::Number(n),
+            // pin the block so both rounds come from the same state
+            _ => BlockTag::Number(self.rpc.block_number().await?),
+        };
+        let reader = FeedReader {
+            policy: None,
+            ..self.clone().at(block)
+        };
+        let Some(proposed) = reader.proposed_aggregator().await? else {
+            return Ok(None);
+        };
+        let aggregator = reader.aggregator().await?;
+        let live = reader.latest_round_data().await?;
+        let proposed_round = reverted_as_none(reader.proposed_latest_round_data().await)?;
+        let live_decimals = reader.decimals().await?;
+        let call = EACAggregatorProxy::decimalsCall {};
+        let proposed_decimals =
+            reverted_as_none(reader.rpc.call(proposed, &call, block).await)?.map(|r| r._0);
+
+        let live_price = Price::from_round(&live, live_decimals);
+        let price_delta = proposed_round.zip(proposed_decimals).and_then(|(round, decimals)| {
+            delta(live_price, Price::from_round(&round, decimals))
+        });
+        let deviation_bps = price_delta.and_then(|delta| {
+            let live = live_price.to_decimal().filter(|live| !live.is_zero())?;
+            delta
+                .to_decimal()?
+                .checked_mul(Decimal::from(10_000))?
+                .checked_div(live.abs())
+                .map(|bps| bps.normalize())
+        });
+
+        Ok(Some(ProposalReport {
+            aggregator,
+            proposed,
+            block,
+            live,
+            proposed_round,
+            live_decimals,
+            proposed_decimals,
+            decimals_match: proposed_decimals == Some(live_decimals),
+            price_delta,
+            deviation_bps,
+            timestamp_delta: proposed_round
+                .map(|round| round.updated_at as i64 - live.updated_at as i64),
+        }))
+    }
+}
+
+/// `to - from` at the larger precision of both
+fn delta(from: Price, to: Price) -> Option<Price> {
+    let decimals = from.decimals.max(to.decimals);
+    let (from, to) = (from.rescale(decimals)?, to.rescale(decimals)?);
+    Some(Price::new(to.answer.checked_sub(from.answer)?, decimals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{call_args, output, reverted, MockFeed, MockNode};
+    use alloy_primitives::{address, I256, U256};
+    use alloy_sol_types::SolCall;
+    use serde_json::json;
+
+    const PROPOSED: Address = address!("00000000000000000000000000000000000000aa");
+
+    /// Live feed answering 3000 at 8 decimals, `proposed_round` is (answer, updatedAt)
+    async fn node(proposed: Option<Address>, proposed_round: Option<(i64, u64)>) -> MockNode {
+        let feed = MockFeed {
+            proxy: address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            phases: vec![(Address::repeat_byte(1), vec![1000, 3000_00000000])],
+        };
+        MockNode::start(move |method, params| {
+            if method == "eth_blockNumber" {
+                return Ok(json!("0x10"));
+            }
+            let (to, data) = call_args(params);
+            let selector: [u8; 4] = data[..4].try_into().unwrap();
+            let out = match (to, selector) {
+                (PROPOSED, EACAggregatorProxy::decimalsCall::SELECTOR) => {
+                    EACAggregatorProxy::decimalsCall::abi_encode_returns(&(8u8,))
+                }
+                (_, EACAggregatorProxy::proposedAggregatorCall::SELECTOR) => {
+                    let proposed = proposed.unwrap_or(Address::ZERO);
+                    EACAggregatorProxy::proposedAggregatorCall::abi_encode_returns(&(proposed,))
+                }
+                (_, EACAggregatorProxy::proposedLatestRoundDataCall::SELECTOR) => {
+                    let (answer, updated_at) = proposed_round.ok_or_else(reverted)?;
+                    let updated_at = U256::from(updated_at);
+                    EACAggregatorProxy::proposedLatestRoundDataCall::abi_encode_returns(&(
+                        7,
+                        I256::try_from(answer).unwrap(),
+                        updated_at,
+                        updated_at,
+                        7,
+                    ))
+                }
+                _ => return feed.eth_call(params),
+            };
+            // every read is pinned to the same block
+            assert_eq!(params[1], json!("0x10"));
+            Ok(output(out))
+        })
+        .await
+    }
+
+    fn reader(node: &MockNode) -> FeedReader {
+        FeedReader::new(
+            &node.url,
+            address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+        )
+    }
+
+    #[tokio::test]
+    async fn nothing_proposed() {
+        let node = node(None, None).await;
+        assert_eq!(reader(&node).proposal_report().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn proposed_with_rounds() {
+        let node = node(Some(PROPOSED), Some((3030_00000000, 3000_00000060))).await;
+        let report = reader(&node).proposal_report().await.unwrap().unwrap();
+        assert_eq!(report.proposed, PROPOSED);
+        assert_eq!(report.aggregator, Address::repeat_byte(1));
+        assert_eq!(report.block, BlockTag::Number(16));
+        assert_eq!(report.proposed_round.unwrap().round_id, 7);
+        assert!(report.decimals_match);
+        assert_eq!(report.live_price().to_string(), "3000.00000000");
+        assert_eq!(
+            report.proposed_price().unwrap().to_string(),
+            "3030.00000000"
+        );
+        assert_eq!(report.price_delta.unwrap().to_string(), "30.00000000");
+        assert_eq!(report.deviation_bps, Some(Decimal::from(100)));
+        assert_eq!(report.timestamp_delta, Some(60));
+    }
+
+    #[tokio::test]
+    async fn proposed_without_rounds() {
+        let node = node(Some(PROPOSED), None).await;
+        let report = reader(&node).proposal_report().await.unwrap().unwrap();
+        assert_eq!(report.proposed, PROPOSED);
+        assert_eq!(report.proposed_round, None);
+        assert_eq!(report.proposed_decimals, Some(8));
+        assert_eq!(report.proposed_price(), None);
+        assert_eq!(report.price_delta, None);
+        assert_eq!(report.deviation_bps, None);
+        assert_eq!(report.timestamp_delta, None);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Oracle, OraclesIndex};
+use alloy_primitives::Address;
+
+/// Result of a pair lookup that also considers the reversed pair
+#[derive(Debug, Clone, Copy)]
+pub enum PairMatch<'a> {
+    /// feed quotes `token` in `base`
+    Direct(&'a Oracle),
+    /// only `base / token` exists, answer has to be inverted
+    Inverse(&'a Oracle),
+}
+
+impl<'a> PairMatch<'a> {
+    pub fn oracle(&self) -> &'a Oracle {
+        match self {
+            PairMatch::Direct(o) | PairMatch::Inverse(o) => o,
+        }
+    }
+
+    pub fn is_inverse(&self) -> bool {
+        matches!(self, PairMatch::Inverse(_))
+    }
+}
+
+impl Oracle {
+    /// (token, base) from the `pair` field, falling back to parsing `name`
+    pub fn symbols(&self) -> Option<(&str, &str)> {
+        if let [token, base] = self.pair.as_slice() {
+            if !token.is_empty() && !base.is_empty() {
+                return Some((token.as_str(), base.as_str()));
+            }
+        }
+        let (token, base) = self.name.as_deref()?.split_once('/')?;
+        Some((token.trim(), base.trim()))
+    }
+
+    /// Case-insensitive match on the quoted pair
+    pub fn is_pair(&self, token: &str, base: &str) -> bool {
+        self.symbols()
+            .map(|(t, b)| t.eq_ignore_ascii_case(token) && b.eq_ignore_ascii_case(base))
+            .unwrap_or(false)
+    }
+}
+
+impl OraclesIndex {
+    /// Feed behind a proxy address
+    pub fn by_proxy(&self, proxy: Address) -> Option<&Oracle> {
+        self.feeds.iter().find(|o| o.proxy_address == Some(proxy))
+    }
+
+    /// Feeds whose underlying aggregator is `aggregator`
+    pub fn by_aggregator(&self, aggregator: Address) -> impl Iterator<Item = &Oracle> {
+        self.feeds
+            .iter()
+            .filter(move |o| o.contract_address == Some(aggregator))
+    }
+
+    /// Feed by ens name, with or without the `.data.eth` suffix
+    pub fn by_ens(&self, ens: &str) -> Option<&Oracle> {
+        let ens = ens.trim();
+        let ens = ens.strip_suffix(".data.eth").unwrap_or(ens);
+        self.feeds.iter().find(|o| {
+            o.ens
+                .as_deref()
+                .map(|e| e.strip_suffix(".data.eth").unwrap_or(e))
+                .map(|e| e.eq_ignore_ascii_case(ens))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Feeds quoting `token` in `base` (case-insensitive), ie: the same pair can exist
+    /// as a price feed and as a different product type
+    pub fn by_pair<'a>(
+        &'a self,
+        token: &'a str,
+        base: &'a str,
+    ) -> impl Iterator<Item = &'a Oracle> {
+        self.feeds.iter().filter(move |o| o.is_pair(token, base))
+    }
+
+    /// Feeds of an asset by name (ie: "Ethereum"), case-insensitive
+    pub fn by_asset_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Oracle> {
+        self.feeds.iter().filter(move |o| {
+            [o.asset_name.as_deref(), o.docs.asset_name.as_deref()]
+                .into_iter()
+                .flatten()
+                .any(|n| n.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Feeds where `symbol` is either side of the pair
+    pub fn by_symbol<'a>(&'a self, symbol: &'a str) -> impl Iterator<Item = &'a Oracle> {
+        self.feeds.iter().filter(move |o| {
+            o.symbols()
+                .map(|(t, b)| t.eq_ignore_ascii_case(symbol) || b.eq_ignore_ascii_case(symbol))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Case-insensitive version of `get_oracle`
+    pub fn find_pair(&self, token: &str, base: &str) -> Option<&Oracle> {
+        self.feeds.iter().find(|o| o.is_pair(token, base))
+    }
+
+    /// Looks for `token / base`, hinting the inverse feed when only `base / token` exists
+    pub fn lookup(&self, token: &str, base: &str) -> Option<PairMatch<'_>> {
+        self.find_pair(token, base)
+            .map(PairMatch::Direct)
+            .or_else(|| self.find_pair(base, token).map(PairMatch::Inverse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+
+    fn index() -> OraclesIndex {
+        let json = r#"[
+            {"name": "ETH / USD", "pair": ["ETH", "USD"], "ens": "eth-usd",
+             "proxyAddress": "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"},
+            {"name": "stETH / ETH", "pair": ["", ""]},
+            {"name": "BTC / ETH", "pair": ["BTC", "ETH"], "assetName": "Bitcoin"}
+        ]"#;
+        OraclesIndex::from_json_str(Chain::mainnet(), json).unwrap()
+    }
+
+    #[test]
+    fn lookup() {
+        let index = index();
+        let direct = index.lookup("eth", "usd").unwrap();
+        assert!(!direct.is_inverse());
+        assert_eq!(direct.oracle().name.as_deref(), Some("ETH / USD"));
+
+        let inverse = index.lookup("USD", "ETH").unwrap();
+        assert!(inverse.is_inverse());
+        assert_eq!(inverse.oracle().name.as_deref(), Some("ETH / USD"));
+
+        // symbols parsed from the name when `pair` is empty
+        let inverse = index.lookup("ETH", "stETH").unwrap();
+        assert!(inverse.is_inverse());
+        assert_eq!(inverse.oracle().symbols(), Some(("stETH", "ETH")));
+
+        assert!(index.lookup("BTC", "USD").is_none());
+    }
+
+    #[test]
+    fn by_fields() {
+        let index = index();
+        let proxy = "0x5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            index.by_proxy(proxy).unwrap().ens.as_deref(),
+            Some("eth-usd")
+        );
+        assert!(index.by_ens("ETH-USD.data.eth").is_some());
+        assert_eq!(index.by_symbol("eth").count(), 3);
+        assert_eq!(index.by_asset_name("bitcoin").count(), 1);
+        assert_eq!(index.by_pair("btc", "eth").count(), 1);
+    }
+}
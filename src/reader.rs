@@ -0,0 +1,219 @@
+use crate::{
+    contracts::EACAggregatorProxy::EACAggregatorProxy,
+    rpc::{BlockTag, RpcClient},
+    validate::RoundPolicy,
+    Error, Oracle, Price, Result,
+};
+use alloy_primitives::{Address, I256, U256};
+
+/// Round as answered by `latestRoundData` / `getRoundData`
+/// round ids are uint80 on chain, timestamps are unix seconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundData {
+    pub round_id: u128,
+    pub answer: I256,
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub answered_in_round: u128,
+}
+
+impl RoundData {
+    pub(crate) fn from_parts(
+        round_id: u128,
+        answer: I256,
+        started_at: U256,
+        updated_at: U256,
+        answered_in_round: u128,
+    ) -> Self {
+        Self {
+            round_id,
+            answer,
+            started_at: started_at.saturating_to(),
+            updated_at: updated_at.saturating_to(),
+            answered_in_round,
+        }
+    }
+}
+
+impl From<EACAggregatorProxy::latestRoundDataReturn> for RoundData {
+    fn from(r: EACAggregatorProxy::latestRoundDataReturn) -> Self {
+        Self::from_parts(r.roundId, r.answer, r.startedAt, r.updatedAt, r.answeredInRound)
+    }
+}
+
+impl From<EACAggregatorProxy::getRoundDataReturn> for RoundData {
+    fn from(r: EACAggregatorProxy::getRoundDataReturn) -> Self {
+        Self::from_parts(r.roundId, r.answer, r.startedAt, r.updatedAt, r.answeredInRound)
+    }
+}
+
+impl From<EACAggregatorProxy::proposedLatestRoundDataReturn> for RoundData {
+    fn from(r: EACAggregatorProxy::proposedLatestRoundDataReturn) -> Self {
+        Self::from_parts(r.roundId, r.answer, r.startedAt, r.updatedAt, r.answeredInRound)
+    }
+}
+
+impl From<EACAggregatorProxy::proposedGetRoundDataReturn> for RoundData {
+    fn from(r: EACAggregatorProxy::proposedGetRoundDataReturn) -> Self {
+        Self::from_parts(r.roundId, r.answer, r.startedAt, r.updatedAt, r.answeredInRound)
+    }
+}
+
+/// Reads a feed through its EACAggregatorProxy with plain `eth_call`s
+/// rounds are checked against `policy` when one is set
+#[derive(Debug, Clone)]
+pub struct FeedReader {
+    pub rpc: RpcClient,
+    pub proxy: Address,
+    pub block: BlockTag,
+    pub policy: Option<RoundPolicy>,
+}
+
+impl FeedReader {
+    pub fn new(rpc_url: impl Into<String>, proxy: Address) -> Self {
+        Self::with_client(RpcClient::new(rpc_url), proxy)
+    }
+
+    pub fn with_client(rpc: RpcClient, proxy: Address) -> Self {
+        Self {
+            rpc,
+            proxy,
+            block: BlockTag::Latest,
+            policy: None,
+        }
+    }
+
+    /// Reader for the proxy of a directory entry
+    pub fn for_oracle(rpc_url: impl Into<String>, oracle: &Oracle) -> Result<Self> {
+        let proxy = oracle.proxy_address.ok_or_else(|| Error::MissingProxy {
+            name: oracle.name.clone(),
+        })?;
+        Ok(Self::new(rpc_url, proxy))
+    }
+
+    /// Runs following calls at `block` instead of latest
+    pub fn at(mut self, block: impl Into<BlockTag>) -> Self {
+        self.block = block.into();
+        self
+    }
+
+    /// Rejects rounds breaking `policy` from now on
+    pub fn with_policy(mut self, policy: RoundPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    pub async fn latest_round_data(&self) -> Result<RoundData> {
+        let call = EACAggregatorProxy::latestRoundDataCall {};
+        let round: RoundData = self.rpc.call(self.proxy, &call, self.block).await?.into();
+        self.check(&round)?;
+        Ok(round)
+    }
+
+    /// Latest answer with the on-chain decimals
+    pub async fn latest_price(&self) -> Result<Price> {
+        let round = self.latest_round_data().await?;
+        Ok(Price::from_round(&round, self.decimals().await?))
+    }
+
+    pub(crate) fn check(&self, round: &RoundData) -> Result<()> {
+        match &self.policy {
+            Some(policy) => policy.check(round),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn decimals(&self) -> Result<u8> {
+        let call = EACAggregatorProxy::decimalsCall {};
+        Ok(self.rpc.call(self.proxy, &call, self.block).await?._0)
+    }
+
+    pub async fn description(&self) -> Result<String> {
+        let call = EACAggregatorProxy::descriptionCall {};
+        Ok(self.rpc.call(self.proxy, &call, self.block).await?._0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{call_args, output, reverted, MockNode};
+    use alloy_primitives::address;
+    use alloy_sol_types::SolCall;
+    use serde_json::json;
+
+    const PROXY: Address = address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
+
+    #[tokio::test]
+    async fn latest_round_data() {
+        let node = MockNode::start(|method, params| {
+            assert_eq!(method, "eth_call");
+            let (_, data) = call_args(params);
+            let out = match data[..4].try_into().unwrap() {
+                EACAggregatorProxy::latestRoundDataCall::SELECTOR => {
+                    EACAggregatorProxy::latestRoundDataCall::abi_encode_returns(&(
+                        (1u128 << 64) | 42,
+                        I256::try_from(300_012_345_678i64).unwrap(),
+                        U256::from(1_700_000_000u64),
+                        U256::from(1_700_000_012u64),
+                        (1u128 << 64) | 42,
+                    ))
+                }
+                EACAggregatorProxy::decimalsCall::SELECTOR => {
+                    EACAggregatorProxy::decimalsCall::abi_encode_returns(&(8u8,))
+                }
+                EACAggregatorProxy::descriptionCall::SELECTOR => return Err(reverted()),
+                selector => panic!("unexpected selector {:?}", selector),
+            };
+            Ok(output(out))
+        })
+        .await;
+        let reader = FeedReader::new(&node.url, PROXY).at(123);
+        let round = reader.latest_round_data().await.unwrap();
+        assert_eq!(
+            round,
+            RoundData {
+                round_id: (1 << 64) | 42,
+                answer: I256::try_from(300_012_345_678i64).unwrap(),
+                started_at: 1_700_000_000,
+                updated_at: 1_700_000_012,
+                answered_in_round: (1 << 64) | 42,
+            }
+        );
+
+        let request = &node.requests()[0];
+        assert_eq!(request["method"], "eth_call");
+        let (to, data) = call_args(&request["params"]);
+        assert_eq!(to, PROXY);
+        assert_eq!(alloy_primitives::hex::encode(data), "feaf968c");
+        assert_eq!(request["params"][1], json!("0x7b"));
+
+        let price = reader.latest_price().await.unwrap();
+        assert_eq!(price.to_string(), "3000.12345678");
+        assert!(matches!(
+            reader.description().await,
+            Err(Error::Rpc { code: 3, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejected_by_policy() {
+        let node = MockNode::start(|_, _| {
+            Ok(output(
+                EACAggregatorProxy::latestRoundDataCall::abi_encode_returns(&(
+                    7u128,
+                    I256::ZERO,
+                    U256::from(1u64),
+                    U256::from(1u64),
+                    7u128,
+                )),
+            ))
+        })
+        .await;
+        let reader = FeedReader::new(&node.url, PROXY).with_policy(RoundPolicy::default());
+        assert!(matches!(
+            reader.latest_round_data().await,
+            Err(Error::InvalidRound { .. })
+        ));
+    }
+}
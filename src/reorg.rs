@@ -0,0 +1,418 @@
+use crate::{
+    logs::{FeedLog, LogScanner, RawLog},
+    rpc::BlockTag,
+    Error, FeedReader, Result,
+};
+use alloy_primitives::B256;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Blocks on top of a log's block (its own included) before it is emitted
+pub const DEFAULT_CONFIRMATIONS: u64 = 12;
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReorgEvent {
+    /// log buried under enough blocks, emitted once
+    Confirmed(FeedLog),
+    /// log seen earlier that is gone from the chain (`removed` set) or now sits in a
+    /// block with another hash, it never reaches `Confirmed`
+    Removed(FeedLog),
+}
+
+/// Where to resume from, every log before `next_block` was already confirmed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogCursor {
+    pub next_block: u64,
+    /// hash of `next_block - 1` when it was confirmed
+    pub last_hash: Option<B256>,
+}
+
+impl LogCursor {
+    pub fn new(from_block: u64) -> Self {
+        Self {
+            next_block: from_block,
+            last_hash: None,
+        }
+    }
+
+    /// None when nothing was saved at `path` yet
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let body = match fs::read_to_string(path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        crate::error::decode(&body).map(Some)
+    }
+
+    /// Written to a temp file renamed over `path`, a crash never leaves half a cursor
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let body = serde_json::to_vec(self).map_err(io::Error::from)?;
+        crate::write_atomic(path.as_ref(), &body)
+    }
+}
+
+/// Follows the logs of a `LogScanner`, holding them back until they have
+/// `confirmations` blocks and reporting the ones a reorg took away
+/// unconfirmed logs are not persisted, they are found again from the cursor
+/// the aggregator set is the scanner's, only followers made by `follow_events` add the
+/// aggregator of a new phase once the proxy moves to it
+#[derive(Debug, Clone)]
+pub struct EventFollower {
+    pub scanner: LogScanner,
+    pub confirmations: u64,
+    pub cursor: LogCursor,
+    /// file the cursor is saved to after every advance
+    pub cursor_path: Option<PathBuf>,
+    pub poll_interval: Duration,
+    pending: Vec<FeedLog>,
+    /// proxy whose `phaseId` is checked on every poll
+    reader: Option<FeedReader>,
+}
+
+impl FeedReader {
+    /// Follower over the aggregators of every phase of the proxy, starting at `from_block`
+    /// phases confirmed later are picked up as the proxy reaches them
+    pub async fn follow_events(&self, from_block: u64) -> Result<EventFollower> {
+        let reader = self.clone().at(BlockTag::Latest);
+        let mut follower = EventFollower::new(reader.log_scanner().await?, from_block);
+        follower.reader = Some(reader);
+        Ok(follower)
+    }
+}
+
+impl EventFollower {
+    pub fn new(scanner: LogScanner, from_block: u64) -> Self {
+        Self {
+            scanner,
+            confirmations: DEFAULT_CONFIRMATIONS,
+            cursor: LogCursor::new(from_block),
+            cursor_path: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            pending: Vec::new(),
+            reader: None,
+        }
+    }
+
+    pub fn confirmations(mut self, blocks: u64) -> Self {
+        self.confirmations = blocks.max(1);
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Saves the cursor at `path`, resuming from the one already there if any
+    pub fn with_cursor_file(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if let Some(cursor) = LogCursor::load(&path)? {
+            self.cursor = cursor;
+            self.pending.clear();
+        }
+        self.cursor_path = Some(path);
+        Ok(self)
+    }
+
+    /// Logs seen but not confirmed yet
+    pub fn pending(&self) -> &[FeedLog] {
+        &self.pending
+    }
+
+    /// Scans from the cursor to the head once
+    /// fails with `ReorgTooDeep` when the last confirmed block was replaced, the logs
+    /// emitted for it can't be trusted anymore (set a new `cursor` to carry on)
+    pub async fn poll(&mut self) -> Result<Vec<ReorgEvent>> {
+        let rpc = &self.scanner.rpc;
+        let head = rpc.block_number().await?;
+        if let Some(hash) = self.cursor.last_hash {
+            let block_number = self.cursor.next_block - 1;
+            if rpc.block_hash(BlockTag::Number(block_number)).await? != hash {
+                return Err(Error::ReorgTooDeep { block_number });
+            }
+        }
+        if head < self.cursor.next_block {
+            return Ok(Vec::new());
+        }
+        self.refresh_phases().await?;
+        let rpc = &self.scanner.rpc;
+        let fresh = self.scanner.raw_logs(self.cursor.next_block, head).await?;
+        let mut events = Vec::new();
+
+        // pending logs the node no longer returns from the same block were reorged out
+        let current: HashMap<(u64, u64), &RawLog> = fresh
+            .iter()
+            .filter(|log| !log.removed)
+            .map(|log| ((log.block_number, log.log_index), log))
+            .collect();
+        let mut pending = Vec::with_capacity(self.pending.len());
+        for log in self.pending.drain(..) {
+            match current.get(&(log.block_number, log.log_index)) {
+                Some(raw) if raw.block_hash == log.block_hash => pending.push(log),
+                _ => events.push(ReorgEvent::Removed(FeedLog {
+                    removed: true,
+                    ..log
+                })),
+            }
+        }
+
+        // first sightings, including new versions of replaced logs
+        let known: HashSet<(B256, u64)> = pending
+            .iter()
+            .map(|log| (log.block_hash, log.log_index))
+            .collect();
+        let new: Vec<RawLog> = fresh
+            .into_iter()
+            .filter(|log| !log.removed && !known.contains(&(log.block_hash, log.log_index)))
+            .collect();
+        pending.extend(self.scanner.resolve(new).await?);
+        pending.sort_by_key(|log| (log.block_number, log.log_index));
+
+        // `safe` and older blocks have `confirmations` blocks on top, head included
+        let safe = (head + 1).saturating_sub(self.confirmations.max(1));
+        if safe >= self.cursor.next_block {
+            let (confirmed, rest): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|log| log.block_number <= safe);
+            events.extend(confirmed.into_iter().map(ReorgEvent::Confirmed));
+            pending = rest;
+            self.cursor = LogCursor {
+                next_block: safe + 1,
+                last_hash: Some(rpc.block_hash(BlockTag::Number(safe)).await?),
+            };
+            if let Some(path) = &self.cursor_path {
+                self.cursor.save(path)?;
+            }
+        }
+        self.pending = pending;
+        Ok(events)
+    }
+
+    /// Adds the aggregator of a phase the proxy moved to since the last poll
+    /// read after the head, so its logs up to the head are in this poll's scan
+    async fn refresh_phases(&mut self) -> Result<()> {
+        let Some(reader) = &self.reader else {
+            return Ok(());
+        };
+        let phase = reader.phase_id().await?;
+        if !self.scanner.phases.values().any(|known| *known == phase) {
+            self.scanner.set_phases(&reader.phases().await?);
+        }
+        Ok(())
+    }
+
+    /// Polls every `poll_interval` forever, errors are yielded and polling goes on
+    pub fn stream(self) -> BoxStream<'static, Result<ReorgEvent>> {
+        stream::unfold((self, true), |(mut follower, first)| async move {
+            if !first {
+                tokio::time::sleep(follower.poll_interval).await;
+            }
+            let events = follower
+                .poll()
+                .await
+                .map(|events| stream::iter(events.into_iter().map(Ok)));
+            Some((events, (follower, false)))
+        })
+        .try_flatten()
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        rpc::parse_quantity,
+        testing::{answer_log, MockFeed, MockNode},
+    };
+    use alloy_primitives::{address, Address};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    /// chain of `head` blocks, the ones from `fork_at` on hashed per `fork`
+    struct Chain {
+        head: u64,
+        fork: u8,
+        fork_at: u64,
+        feed: MockFeed,
+        /// (aggregator, aggregator round, block)
+        logs: Vec<(Address, u64, u64)>,
+    }
+
+    impl Chain {
+        fn hash(&self, block: u64) -> B256 {
+            let fork = if block >= self.fork_at { self.fork } else { 0 };
+            let mut hash = B256::with_last_byte(fork);
+            hash[..8].copy_from_slice(&block.to_be_bytes());
+            hash
+        }
+
+        fn answer(
+            &self,
+            method: &str,
+            params: &Value,
+        ) -> std::result::Result<Value, (i64, String)> {
+            let block = |param: &Value| parse_quantity(param.as_str().unwrap()).unwrap();
+            match method {
+                "eth_blockNumber" => Ok(json!(format!("{:#x}", self.head))),
+                "eth_getBlockByNumber" => {
+                    let n = block(&params[0]);
+                    Ok(json!({"hash": self.hash(n), "timestamp": format!("{:#x}", 10 * n)}))
+                }
+                "eth_getLogs" => {
+                    let (from, to) = (block(&params[0]["fromBlock"]), block(&params[0]["toBlock"]));
+                    let addresses: Vec<Address> =
+                        serde_json::from_value(params[0]["address"].clone()).unwrap();
+                    let logs = self
+                        .logs
+                        .iter()
+                        .filter(|(a, _, n)| addresses.contains(a) && (from..=to).contains(n))
+                        .map(|(a, round, n)| answer_log(*a, *round, *n, self.hash(*n)))
+                        .collect();
+                    Ok(Value::Array(logs))
+                }
+                "eth_call" => self.feed.eth_call(params),
+                _ => Err((-32601, "method not found".to_string())),
+            }
+        }
+    }
+
+    async fn node(chain: &Arc<Mutex<Chain>>) -> MockNode {
+        let chain = chain.clone();
+        MockNode::start(move |method, params| chain.lock().unwrap().answer(method, params)).await
+    }
+
+    fn confirmed(events: &[ReorgEvent]) -> Vec<(Option<u16>, u64)> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                ReorgEvent::Confirmed(log) => Some((log.phase, log.block_number)),
+                ReorgEvent::Removed(_) => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn follows_new_phases() {
+        let (first, second) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let proxy = address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
+        let chain = Arc::new(Mutex::new(Chain {
+            head: 20,
+            fork: 0,
+            fork_at: 0,
+            feed: MockFeed {
+                proxy,
+                phases: vec![(first, vec![100])],
+            },
+            logs: vec![(first, 1, 5)],
+        }));
+        let node = node(&chain).await;
+        let mut follower = FeedReader::new(&node.url, proxy)
+            .follow_events(1)
+            .await
+            .unwrap()
+            .confirmations(5);
+
+        assert_eq!(
+            confirmed(&follower.poll().await.unwrap()),
+            vec![(Some(1), 5)]
+        );
+        assert_eq!(follower.cursor.next_block, 17);
+
+        // confirmAggregator at block 22, first answer of the new aggregator at 23
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.feed.phases.push((second, vec![200]));
+            chain.logs.push((second, 1, 23));
+            chain.head = 30;
+        }
+        assert_eq!(
+            confirmed(&follower.poll().await.unwrap()),
+            vec![(Some(2), 23)]
+        );
+        assert_eq!(follower.scanner.aggregators, vec![first, second]);
+    }
+
+    #[tokio::test]
+    async fn reports_reorged_logs() {
+        let aggregator = Address::repeat_byte(1);
+        let chain = Arc::new(Mutex::new(Chain {
+            head: 10,
+            fork: 0,
+            fork_at: 0,
+            feed: MockFeed {
+                proxy: Address::ZERO,
+                phases: Vec::new(),
+            },
+            logs: vec![(aggregator, 1, 9)],
+        }));
+        let node = node(&chain).await;
+        let scanner = LogScanner::new(crate::RpcClient::new(&node.url), vec![aggregator]);
+        let mut follower = EventFollower::new(scanner, 1).confirmations(5);
+
+        assert!(follower.poll().await.unwrap().is_empty());
+        assert_eq!(follower.pending().len(), 1);
+
+        // blocks 8.. replaced, the log moved to block 11
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.fork = 1;
+            chain.fork_at = 8;
+            chain.logs = vec![(aggregator, 1, 11)];
+            chain.head = 16;
+        }
+        let events = follower.poll().await.unwrap();
+        let [ReorgEvent::Removed(removed), ReorgEvent::Confirmed(_)] = events[..] else {
+            panic!("unexpected {:?}", events);
+        };
+        assert_eq!((removed.block_number, removed.removed), (9, true));
+        assert_eq!(confirmed(&events), vec![(None, 11)]);
+
+        // the last confirmed block itself replaced
+        {
+            let mut chain = chain.lock().unwrap();
+            chain.fork = 2;
+            chain.fork_at = 12;
+        }
+        assert!(matches!(
+            follower.poll().await,
+            Err(Error::ReorgTooDeep { block_number: 12 })
+        ));
+    }
+
+    #[test]
+    fn cursor_files() {
+        let dir = std::env::temp_dir().join(format!("datafeeds-cursor-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cursor.json");
+        assert_eq!(LogCursor::load(&path).unwrap(), None);
+
+        let cursor = LogCursor {
+            next_block: 120,
+            last_hash: Some(B256::repeat_byte(7)),
+        };
+        cursor.save(&path).unwrap();
+        LogCursor::new(1).save(&path).unwrap();
+        cursor.save(&path).unwrap();
+        assert_eq!(LogCursor::load(&path).unwrap(), Some(cursor));
+        // only the renamed file is left
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        fs::write(&path, r#"{"next_block": 1"#).unwrap();
+        assert!(matches!(LogCursor::load(&path), Err(Error::Decode { .. })));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
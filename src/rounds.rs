@@ -0,0 +1,303 @@
+use crate::{
+    contracts::EACAggregatorProxy::EACAggregatorProxy,
+    phases::{next_round_id, previous_round_id, Phase},
+    rpc::is_revert,
+    Error, FeedReader, Result, RoundData,
+};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+
+/// `getRoundData` calls sent per JSON-RPC batch
+pub const DEFAULT_BATCH_SIZE: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// oldest round first
+    #[default]
+    Forward,
+    /// newest round first
+    Backward,
+}
+
+/// End of a round range, both ends are inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundBound {
+    /// proxy (phase encoded) round id
+    RoundId(u128),
+    /// unix seconds, compared with `updated_at`
+    Timestamp(u64),
+}
+
+/// Rounds of a feed between two bounds, walked across phases
+/// `from` is the older end and `to` the newer one whatever the direction,
+/// open ends go to the first / latest round of the feed
+#[derive(Debug, Clone)]
+pub struct RoundRange {
+    pub reader: FeedReader,
+    pub direction: Direction,
+    pub from: Option<RoundBound>,
+    pub to: Option<RoundBound>,
+    pub batch_size: usize,
+}
+
+impl FeedReader {
+    /// Every round of the feed, oldest first, see `RoundRange`
+    pub fn rounds(&self) -> RoundRange {
+        RoundRange::new(self.clone())
+    }
+}
+
+impl RoundRange {
+    pub fn new(reader: FeedReader) -> Self {
+        Self {
+            reader,
+            direction: Direction::Forward,
+            from: None,
+            to: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    pub fn forward(mut self) -> Self {
+        self.direction = Direction::Forward;
+        self
+    }
+
+    pub fn backward(mut self) -> Self {
+        self.direction = Direction::Backward;
+        self
+    }
+
+    pub fn from(mut self, bound: RoundBound) -> Self {
+        self.from = Some(bound);
+        self
+    }
+
+    pub fn to(mut self, bound: RoundBound) -> Self {
+        self.to = Some(bound);
+        self
+    }
+
+    pub fn between_rounds(self, from: u128, to: u128) -> Self {
+        self.from(RoundBound::RoundId(from))
+            .to(RoundBound::RoundId(to))
+    }
+
+    /// Rounds updated between `from` and `to` (unix seconds)
+    pub fn between_timestamps(self, from: u64, to: u64) -> Self {
+        self.from(RoundBound::Timestamp(from))
+            .to(RoundBound::Timestamp(to))
+    }
+
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// Streams the rounds, fetching `batch_size` of them per JSON-RPC batch
+    /// rounds that don't exist are skipped, so are the ones breaking the reader policy
+    /// (instead of failing like `get_round_data` does)
+    /// a round that can't be read is yielded as its error and the walk goes on, a failed
+    /// batch ends the stream
+    pub fn stream(self) -> BoxStream<'static, Result<RoundData>> {
+        stream::once(self.walk())
+            .map_ok(|walk| stream::try_unfold(walk, Walk::next_batch))
+            .try_flatten()
+            .map_ok(stream::iter)
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Resolves the phases and the round to start from
+    async fn walk(self) -> Result<Walk> {
+        // bounds are looked up without the policy, an invalid round is still a position
+        let lookup = FeedReader {
+            policy: None,
+            ..self.reader.clone()
+        };
+        let phases = lookup.phases().await?;
+        let first = phases.iter().find_map(Phase::first_round_id);
+        let last = phases.iter().rev().find_map(Phase::last_round_id);
+        let (next, stop) = match self.direction {
+            Direction::Forward => {
+                let next = match self.from {
+                    None => first,
+                    Some(RoundBound::RoundId(id)) => Some(id),
+                    Some(RoundBound::Timestamp(ts)) => match lookup.round_at_timestamp(ts).await {
+                        Ok(round) if round.updated_at >= ts => Some(round.round_id),
+                        Ok(round) => next_round_id(&phases, round.round_id),
+                        Err(Error::BeforeFirstRound { .. }) => first,
+                        Err(e) => return Err(e),
+                    },
+                };
+                (next, self.to)
+            }
+            Direction::Backward => {
+                let next = match self.to {
+                    None => last,
+                    Some(RoundBound::RoundId(id)) => Some(id),
+                    Some(RoundBound::Timestamp(ts)) => match lookup.round_at_timestamp(ts).await {
+                        Ok(round) => Some(round.round_id),
+                        Err(Error::BeforeFirstRound { .. }) => None,
+                        Err(e) => return Err(e),
+                    },
+                };
+                (next, self.from)
+            }
+        };
+        Ok(Walk {
+            reader: self.reader,
+            phases,
+            direction: self.direction,
+            next,
+            stop,
+            batch_size: self.batch_size.max(1),
+        })
+    }
+}
+
+struct Walk {
+    reader: FeedReader,
+    phases: Vec<Phase>,
+    direction: Direction,
+    next: Option<u128>,
+    stop: Option<RoundBound>,
+    batch_size: usize,
+}
+
+impl Walk {
+    fn step(&self, round_id: u128) -> Option<u128> {
+        match self.direction {
+            Direction::Forward => next_round_id(&self.phases, round_id),
+            Direction::Backward => previous_round_id(&self.phases, round_id),
+        }
+    }
+
+    fn past_round(&self, round_id: u128) -> bool {
+        match (self.stop, self.direction) {
+            (Some(RoundBound::RoundId(stop)), Direction::Forward) => round_id > stop,
+            (Some(RoundBound::RoundId(stop)), Direction::Backward) => round_id < stop,
+            _ => false,
+        }
+    }
+
+    fn past_timestamp(&self, round: &RoundData) -> bool {
+        match (self.stop, self.direction) {
+            (Some(RoundBound::Timestamp(stop)), Direction::Forward) => round.updated_at > stop,
+            (Some(RoundBound::Timestamp(stop)), Direction::Backward) => round.updated_at < stop,
+            _ => false,
+        }
+    }
+
+    /// Next batch of rounds, None once the range is exhausted
+    async fn next_batch(mut self) -> Result<Option<(Vec<Result<RoundData>>, Self)>> {
+        let mut ids = Vec::with_capacity(self.batch_size);
+        while ids.len() < self.batch_size {
+            let Some(id) = self.next.filter(|id| !self.past_round(*id)) else {
+                self.next = None;
+                break;
+            };
+            ids.push(id);
+            self.next = self.step(id);
+        }
+        if ids.is_empty() {
+            return Ok(None);
+        }
+
+        let calls: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                let call = EACAggregatorProxy::getRoundDataCall { _roundId: *id };
+                (self.reader.proxy, call)
+            })
+            .collect();
+        let results = self.reader.rpc.call_batch(&calls, self.reader.block).await?;
+
+        let mut rounds = Vec::with_capacity(results.len());
+        for result in results {
+            // same as `try_round_data`: reverts and zeroed rounds don't exist,
+            // other failures are yielded in place of the round
+            let round: RoundData = match result {
+                Ok(r) => r.into(),
+                Err(e) if is_revert(&e) => continue,
+                Err(e) => {
+                    rounds.push(Err(e));
+                    continue;
+                }
+            };
+            if round.updated_at == 0 {
+                continue;
+            }
+            if self.past_timestamp(&round) {
+                self.next = None;
+                break;
+            }
+            if self.reader.check(&round).is_ok() {
+                rounds.push(Ok(round));
+            }
+        }
+        Ok(Some((rounds, self)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        phases::compose_round_id,
+        testing::{call_args, reverted, MockFeed, MockNode},
+    };
+    use alloy_primitives::{address, Address};
+    use alloy_sol_types::SolCall;
+
+    #[tokio::test]
+    async fn walks_phases_and_yields_failures() {
+        let mock = MockFeed {
+            proxy: address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            phases: vec![
+                (Address::repeat_byte(1), vec![100, 200, 300]),
+                (Address::repeat_byte(2), vec![]),
+                (Address::repeat_byte(3), vec![400, 500, 600]),
+            ],
+        };
+        let proxy = mock.proxy;
+        let node = MockNode::start(move |_, params| {
+            let (_, data) = call_args(params);
+            match EACAggregatorProxy::getRoundDataCall::abi_decode(&data, true) {
+                Ok(call) if call._roundId == compose_round_id(1, 2) => Err(reverted()),
+                Ok(call) if call._roundId == compose_round_id(3, 2) => {
+                    Err((-32005, "request rate limited".to_string()))
+                }
+                _ => mock.eth_call(params),
+            }
+        })
+        .await;
+        let reader = FeedReader::new(&node.url, proxy);
+
+        let rounds: Vec<_> = reader.rounds().batch_size(2).stream().collect().await;
+        let ids: Vec<_> = rounds
+            .iter()
+            .map(|r| r.as_ref().map(|r| r.round_id).map_err(|_| ()))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                Ok(compose_round_id(1, 1)),
+                Ok(compose_round_id(1, 3)),
+                Ok(compose_round_id(3, 1)),
+                Err(()),
+                Ok(compose_round_id(3, 3)),
+            ]
+        );
+        assert!(matches!(rounds[3], Err(Error::Rpc { code: -32005, .. })));
+
+        let rounds: Vec<_> = reader
+            .rounds()
+            .backward()
+            .between_rounds(compose_round_id(1, 1), compose_round_id(3, 1))
+            .stream()
+            .map(|r| r.unwrap().updated_at)
+            .collect()
+            .await;
+        assert_eq!(rounds, vec![400, 300, 100]);
+    }
+}
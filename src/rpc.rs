@@ -0,0 +1,433 @@
+use crate::{Error, Result};
+use alloy_primitives::{hex, Address, Bytes, B256};
+use alloy_sol_types::SolCall;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+/// Block to run a call against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockTag {
+    #[default]
+    Latest,
+    Pending,
+    Safe,
+    Finalized,
+    Earliest,
+    Number(u64),
+}
+
+impl BlockTag {
+    pub(crate) fn to_param(self) -> Value {
+        match self {
+            BlockTag::Latest => json!("latest"),
+            BlockTag::Pending => json!("pending"),
+            BlockTag::Safe => json!("safe"),
+            BlockTag::Finalized => json!("finalized"),
+            BlockTag::Earliest => json!("earliest"),
+            BlockTag::Number(n) => json!(format!("{:#x}", n)),
+        }
+    }
+}
+
+impl From<u64> for BlockTag {
+    fn from(n: u64) -> Self {
+        BlockTag::Number(n)
+    }
+}
+
+/// Minimal JSON-RPC client over http
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    pub url: String,
+    client: reqwest::Client,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Reuses an existing http client (timeouts, proxies..)
+    pub fn with_client(url: impl Into<String>, client: reqwest::Client) -> Self {
+        Self {
+            url: url.into(),
+            client,
+        }
+    }
+
+    /// Raw request, returns the decoded `result`
+    pub async fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let response = self.client.post(&self.url).json(&body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status,
+                url: self.url.clone(),
+            });
+        }
+        let text = response.text().await?;
+        let response: Value = crate::error::decode(&text)?;
+        parse_response(response)
+    }
+
+    /// JSON-RPC batch, one result per request in the same order
+    /// transport failures fail the whole batch, rpc errors only their entry
+    pub async fn batch(&self, requests: &[(&str, Value)]) -> Result<Vec<Result<Value>>> {
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+        let body: Vec<Value> = requests
+            .iter()
+            .enumerate()
+            .map(|(id, (method, params))| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": method,
+                    "params": params,
+                })
+            })
+            .collect();
+        let response = self.client.post(&self.url).json(&body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::HttpStatus {
+                status,
+                url: self.url.clone(),
+            });
+        }
+        let text = response.text().await?;
+        let responses: Vec<Value> = crate::error::decode(&text)?;
+        // answers may come in any order
+        let mut results: Vec<Option<Result<Value>>> = (0..requests.len()).map(|_| None).collect();
+        for response in responses {
+            let id = response.get("id").and_then(Value::as_u64);
+            if let Some(slot) = id.and_then(|id| results.get_mut(id as usize)) {
+                *slot = Some(parse_response(response));
+            }
+        }
+        Ok(results
+            .into_iter()
+            .map(|r| {
+                r.unwrap_or_else(|| Err(Error::InvalidResponse("missing batch entry".to_string())))
+            })
+            .collect())
+    }
+
+    /// Runs many calls in a single JSON-RPC batch
+    pub async fn call_batch<C: SolCall>(
+        &self,
+        calls: &[(Address, C)],
+        block: BlockTag,
+    ) -> Result<Vec<Result<C::Return>>> {
+        let raw: Vec<(Address, Vec<u8>)> = calls
+            .iter()
+            .map(|(to, call)| (*to, call.abi_encode()))
+            .collect();
+        let results = self.eth_call_batch(&raw, block).await?;
+        Ok(results
+            .into_iter()
+            .zip(calls)
+            .map(|(r, (to, _))| decode_returns::<C>(*to, &r?))
+            .collect())
+    }
+
+    /// `eth_call`s with raw calldata in a single JSON-RPC batch
+    pub async fn eth_call_batch(
+        &self,
+        calls: &[(Address, Vec<u8>)],
+        block: BlockTag,
+    ) -> Result<Vec<Result<Bytes>>> {
+        let requests: Vec<(&str, Value)> = calls
+            .iter()
+            .map(|(to, data)| ("eth_call", call_params(*to, data, block)))
+            .collect();
+        let results = self.batch(&requests).await?;
+        Ok(results
+            .into_iter()
+            .map(|r| {
+                let out: String = serde_json::from_value(r?).map_err(|source| Error::Decode {
+                    path: "result".to_string(),
+                    source,
+                })?;
+                decode_bytes(&out)
+            })
+            .collect())
+    }
+
+    /// `eth_call` returning the raw output
+    pub async fn eth_call(&self, to: Address, data: &[u8], block: BlockTag) -> Result<Bytes> {
+        let out: String = self
+            .request("eth_call", call_params(to, data, block))
+            .await?;
+        decode_bytes(&out)
+    }
+
+    /// ABI encodes `call`, runs it through `eth_call` and decodes the returns
+    pub async fn call<C: SolCall>(
+        &self,
+        to: Address,
+        call: &C,
+        block: BlockTag,
+    ) -> Result<C::Return> {
+        let out = self.eth_call(to, &call.abi_encode(), block).await?;
+        decode_returns::<C>(to, &out)
+    }
+
+    pub async fn block_number(&self) -> Result<u64> {
+        let n: String = self.request("eth_blockNumber", json!([])).await?;
+        parse_quantity(&n)
+    }
+
+    /// Unix timestamp of a block
+    pub async fn block_timestamp(&self, block: BlockTag) -> Result<u64> {
+        let header: Option<Value> = self
+            .request("eth_getBlockByNumber", json!([block.to_param(), false]))
+            .await?;
+        header_timestamp(header.as_ref(), block)
+    }
+
+    /// Hash of a block, to notice when it gets reorged out
+    pub async fn block_hash(&self, block: BlockTag) -> Result<B256> {
+        let header: Option<Value> = self
+            .request("eth_getBlockByNumber", json!([block.to_param(), false]))
+            .await?;
+        let hash = header
+            .as_ref()
+            .and_then(|h| h.get("hash"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::InvalidResponse(format!("block {:?} not found", block)))?;
+        hash.parse()
+            .map_err(|e| Error::InvalidResponse(format!("invalid block hash `{}`: {}", hash, e)))
+    }
+
+    /// Timestamps of many blocks in a single JSON-RPC batch, in the same order
+    pub async fn block_timestamps(&self, blocks: &[u64]) -> Result<Vec<u64>> {
+        let requests: Vec<(&str, Value)> = blocks
+            .iter()
+            .map(|n| {
+                let param = BlockTag::Number(*n).to_param();
+                ("eth_getBlockByNumber", json!([param, false]))
+            })
+            .collect();
+        let headers = self.batch(&requests).await?;
+        headers
+            .into_iter()
+            .zip(blocks)
+            .map(|(header, n)| header_timestamp(Some(&header?), BlockTag::Number(*n)))
+            .collect()
+    }
+}
+
+fn header_timestamp(header: Option<&Value>, block: BlockTag) -> Result<u64> {
+    let timestamp = header
+        .and_then(|h| h.get("timestamp"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::InvalidResponse(format!("block {:?} not found", block)))?;
+    parse_quantity(timestamp)
+}
+
+/// Decoded call output, no output at all is `Error::EmptyOutput`
+pub(crate) fn decode_returns<C: SolCall>(to: Address, out: &[u8]) -> Result<C::Return> {
+    match C::abi_decode_returns(out, true) {
+        Ok(r) => Ok(r),
+        Err(_) if out.is_empty() => Err(Error::EmptyOutput { to }),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn call_params(to: Address, data: &[u8], block: BlockTag) -> Value {
+    let tx = json!({
+        "to": to.to_string(),
+        "data": hex::encode_prefixed(data),
+    });
+    json!([tx, block.to_param()])
+}
+
+/// Extracts `result` or the json-rpc `error` of a response
+pub(crate) fn parse_response<T: DeserializeOwned>(mut response: Value) -> Result<T> {
+    if let Some(error) = response.get("error").filter(|e| !e.is_null()) {
+        return Err(Error::Rpc {
+            code: error
+                .get("code")
+                .and_then(Value::as_i64)
+                .unwrap_or_default(),
+            message: error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        });
+    }
+    let result = response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| Error::InvalidResponse("missing result".to_string()))?;
+    serde_json::from_value(result).map_err(|source| Error::Decode {
+        path: "result".to_string(),
+        source,
+    })
+}
+
+/// Whether the contract reverted the call: geth answers code 3, other nodes
+/// only say so in the message
+pub(crate) fn is_revert(error: &Error) -> bool {
+    match error {
+        Error::Rpc { code, message } => {
+            *code == 3 || message.to_lowercase().contains("execution reverted")
+        }
+        _ => false,
+    }
+}
+
+/// Call to a function the contract may not have: None when it reverted or returned
+/// nothing, other failures are kept
+pub(crate) fn reverted_as_none<T>(res: Result<T>) -> Result<Option<T>> {
+    match res {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if is_revert(&e) || matches!(e, Error::EmptyOutput { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub(crate) fn decode_bytes(s: &str) -> Result<Bytes> {
+    hex::decode(s)
+        .map(Bytes::from)
+        .map_err(|e| Error::InvalidResponse(format!("invalid hex `{}`: {}", s, e)))
+}
+
+/// Hex quantity ("0x1b4") to u64
+pub(crate) fn parse_quantity(s: &str) -> Result<u64> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    u64::from_str_radix(digits, 16)
+        .map_err(|e| Error::InvalidResponse(format!("invalid quantity `{}`: {}", s, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockNode;
+
+    #[test]
+    fn quantities() {
+        assert_eq!(parse_quantity("0x1b4").unwrap(), 436);
+        assert_eq!(parse_quantity("0x0").unwrap(), 0);
+        assert_eq!(parse_quantity("ff").unwrap(), 255);
+        assert!(matches!(
+            parse_quantity("0x"),
+            Err(Error::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            parse_quantity("0xzz"),
+            Err(Error::InvalidResponse(_))
+        ));
+        assert!(parse_quantity("0x10000000000000000").is_err());
+    }
+
+    #[test]
+    fn error_objects() {
+        let response = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32005, "message": "rate limited"}});
+        match parse_response::<String>(response) {
+            Err(Error::Rpc { code, message }) => {
+                assert_eq!(code, -32005);
+                assert_eq!(message, "rate limited");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let response = json!({"jsonrpc": "2.0", "id": 1, "error": null, "result": "0x01"});
+        assert_eq!(parse_response::<String>(response).unwrap(), "0x01");
+
+        let response = json!({"jsonrpc": "2.0", "id": 1});
+        assert!(matches!(
+            parse_response::<String>(response),
+            Err(Error::InvalidResponse(_))
+        ));
+
+        let response = json!({"jsonrpc": "2.0", "id": 1, "result": 7});
+        assert!(matches!(
+            parse_response::<String>(response),
+            Err(Error::Decode { .. })
+        ));
+    }
+
+    #[test]
+    fn reverts() {
+        let rpc = |code, message: &str| Error::Rpc {
+            code,
+            message: message.to_string(),
+        };
+        assert!(is_revert(&rpc(3, "execution reverted")));
+        assert!(is_revert(&rpc(
+            -32000,
+            "Execution reverted: No data present"
+        )));
+        assert!(!is_revert(&rpc(-32005, "limit exceeded")));
+        assert!(!is_revert(&rpc(-32000, "header not found")));
+        assert!(!is_revert(&rpc(-32000, "missing trie node 0x12ab")));
+        assert!(!is_revert(&Error::InvalidResponse(
+            "execution reverted".to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn batch_out_of_order() {
+        // answers reversed, the entry of id 1 missing, and an error for id 0
+        let node = MockNode::raw(|body| {
+            let mut responses: Vec<Value> = body
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|r| r["id"] != 1)
+                .map(|r| match r["id"].as_u64() {
+                    Some(0) => {
+                        json!({"id": 0, "error": {"code": 3, "message": "execution reverted"}})
+                    }
+                    _ => json!({"id": r["id"], "result": r["params"][0]}),
+                })
+                .collect();
+            responses.reverse();
+            Value::Array(responses)
+        })
+        .await;
+        let rpc = RpcClient::new(&node.url);
+        let requests: Vec<(&str, Value)> = (0..4).map(|i| ("echo", json!([i]))).collect();
+        let results = rpc.batch(&requests).await.unwrap();
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], Err(Error::Rpc { code: 3, .. })));
+        assert!(matches!(results[1], Err(Error::InvalidResponse(_))));
+        assert_eq!(results[2].as_ref().unwrap(), &json!(2));
+        assert_eq!(results[3].as_ref().unwrap(), &json!(3));
+        assert!(rpc.batch(&[]).await.unwrap().is_empty());
+        assert_eq!(node.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn block_timestamps() {
+        let node = MockNode::start(|method, params| {
+            assert_eq!(method, "eth_getBlockByNumber");
+            let n = parse_quantity(params[0].as_str().unwrap()).unwrap();
+            Ok(json!({"number": params[0], "timestamp": format!("{:#x}", 1000 + n)}))
+        })
+        .await;
+        let rpc = RpcClient::new(&node.url);
+        assert_eq!(
+            rpc.block_timestamps(&[5, 1, 9]).await.unwrap(),
+            vec![1005, 1001, 1009]
+        );
+        assert_eq!(
+            rpc.block_timestamp(BlockTag::Number(2)).await.unwrap(),
+            1002
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{Oracle, OraclesIndex};
+
+/// Oracle matched by `OraclesIndex::search`, score in (0, 1]
+#[derive(Debug, Clone, Copy)]
+pub struct SearchMatch<'a> {
+    pub oracle: &'a Oracle,
+    pub score: f64,
+}
+
+/// Lowercase alphanumeric words, any other char is a separator ("stETH/ETH" -> steth, eth)
+fn tokenize(s: &str) -> Vec<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// How well a query word matches a feed word
+fn token_score(query: &str, candidate: &str) -> f64 {
+    if query == candidate {
+        1.0
+    } else if candidate.starts_with(query) {
+        0.8
+    } else if candidate.contains(query) {
+        0.6
+    } else if query.len() > 3 && levenshtein(query, candidate) <= 1 {
+        // one typo
+        0.5
+    } else {
+        0.0
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+/// Weight of asset name words after the first ("Ether" in "Lido Staked Ether"),
+/// so they don't outrank the symbols or the start of another feed's asset name
+const BURIED_WORD: f64 = 0.5;
+
+/// Scores an oracle against the query words, 0 when any word matches nothing
+fn score(oracle: &Oracle, query: &[String]) -> f64 {
+    let mut words: Vec<(String, f64)> = Vec::new();
+    for field in [
+        oracle.name.as_deref(),
+        oracle.docs.base_asset.as_deref(),
+        oracle.docs.quote_asset.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .chain(oracle.pair.iter().map(String::as_str))
+    {
+        words.extend(tokenize(field).into_iter().map(|w| (w, 1.0)));
+    }
+    for name in [
+        oracle.asset_name.as_deref(),
+        oracle.docs.asset_name.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        words.extend(
+            tokenize(name)
+                .into_iter()
+                .enumerate()
+                .map(|(i, w)| (w, if i == 0 { 1.0 } else { BURIED_WORD })),
+        );
+    }
+    if words.is_empty() {
+        return 0.0;
+    }
+
+    let mut total = 0.0;
+    for q in query {
+        let best = words
+            .iter()
+            .map(|(w, weight)| token_score(q, w) * weight)
+            .fold(0.0, f64::max);
+        if best == 0.0 {
+            return 0.0;
+        }
+        total += best;
+    }
+    let mut score = total / query.len() as f64;
+
+    // prefer "eth usd" -> ETH / USD over USD / ETH, and exact names over partial ones
+    if let Some((token, base)) = oracle.symbols() {
+        let pair = [token.to_lowercase(), base.to_lowercase()];
+        if query == pair {
+            score += 0.2;
+        } else if query.first() == Some(&pair[0]) {
+            score += 0.1;
+        }
+    }
+    (score / 1.2).min(1.0)
+}
+
+impl OraclesIndex {
+    /// Ranked fuzzy search over feed names, pairs and asset names
+    /// tolerant to casing and separators ("eth usd", "Ether", "stETH/ETH")
+    pub fn search(&self, query: &str) -> Vec<SearchMatch<'_>> {
+        let query = tokenize(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<SearchMatch<'_>> = self
+            .feeds
+            .iter()
+            .map(|oracle| SearchMatch {
+                oracle,
+                score: score(oracle, &query),
+            })
+            .filter(|m| m.score > 0.0)
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
+
+    /// Best match of `search`, if any
+    pub fn search_best(&self, query: &str) -> Option<&Oracle> {
+        self.search(query).first().map(|m| m.oracle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_chains::Chain;
+
+    fn index() -> OraclesIndex {
+        let json = r#"[
+            {"name": "USD / ETH", "pair": ["USD", "ETH"]},
+            {"name": "stETH / ETH", "pair": ["STETH", "ETH"], "assetName": "Lido Staked Ether"},
+            {"name": "ETH / USD", "pair": ["ETH", "USD"], "assetName": "Ethereum"},
+            {"name": "BTC / USD", "pair": ["BTC", "USD"], "assetName": "Bitcoin"}
+        ]"#;
+        OraclesIndex::from_json_str(Chain::mainnet(), json).unwrap()
+    }
+
+    fn names(index: &OraclesIndex, query: &str) -> Vec<String> {
+        index
+            .search(query)
+            .iter()
+            .filter_map(|m| m.oracle.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn ranking() {
+        let index = index();
+        assert_eq!(names(&index, "eth usd"), ["ETH / USD", "USD / ETH"]);
+        assert_eq!(names(&index, "USD-ETH"), ["USD / ETH", "ETH / USD"]);
+        assert_eq!(
+            index.search_best("stETH/ETH").unwrap().name.as_deref(),
+            Some("stETH / ETH")
+        );
+        // asset names, prefixes and one typo
+        assert_eq!(names(&index, "Ether"), ["ETH / USD", "stETH / ETH"]);
+        assert_eq!(
+            index.search_best("bitcoin").unwrap().name.as_deref(),
+            Some("BTC / USD")
+        );
+        assert_eq!(
+            index.search_best("ethere").unwrap().name.as_deref(),
+            Some("ETH / USD")
+        );
+        assert_eq!(
+            index.search_best("bitcoim").unwrap().name.as_deref(),
+            Some("BTC / USD")
+        );
+
+        let matches = index.search("eth usd");
+        assert!(matches.iter().all(|m| m.score > 0.0 && m.score <= 1.0));
+        assert_eq!(matches[0].score, 1.0);
+    }
+
+    #[test]
+    fn no_match() {
+        let index = index();
+        assert!(index.search("").is_empty());
+        assert!(index.search(" / ").is_empty());
+        // every word has to match something
+        assert!(index.search("eth jpy").is_empty());
+        // typos are only tolerated on longer words
+        assert!(index.search("bta").is_empty());
+    }
+}
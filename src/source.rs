@@ -0,0 +1,257 @@
+use crate::{Error, Result};
+use alloy_chains::{Chain, NamedChain};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::HashMap;
+
+/// Public reference-data-directory
+pub const DEFAULT_BASE_URL: &str = "https://reference-data-directory.vercel.app";
+
+/// Preview deployment hosting the zkSync / Polygon zkEVM feeds
+const PREVIEW_BASE_URL: &str = "https://reference-data-directory-qy7u5hvya-chainlinklabs.vercel.app";
+
+/// Where reference feeds are fetched from
+/// overrides are either a path relative to `base_url` or a full url
+#[derive(Debug, Clone)]
+pub struct ReferenceSource {
+    pub base_url: String,
+    pub overrides: HashMap<NamedChain, String>,
+    pub headers: HeaderMap,
+}
+
+impl Default for ReferenceSource {
+    fn default() -> Self {
+        let overrides = [
+            NamedChain::ZkSync,
+            NamedChain::ZkSyncTestnet,
+            NamedChain::PolygonZkEvm,
+            NamedChain::PolygonZkEvmTestnet,
+        ]
+        .into_iter()
+        .filter_map(|chain| Some((chain, format!("{}/{}", PREVIEW_BASE_URL, feeds_file(chain)?))))
+        .collect();
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            overrides,
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+impl ReferenceSource {
+    /// Default directory layout served from another host (ie: an internal mirror)
+    /// preview overrides are dropped so every chain resolves against `base_url`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            overrides: HashMap::new(),
+            headers: HeaderMap::new(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Replaces the location of a chain feeds, relative path or full url
+    pub fn with_override(mut self, chain: NamedChain, location: impl Into<String>) -> Self {
+        self.overrides.insert(chain, location.into());
+        self
+    }
+
+    /// Header sent along every request (ie: auth for a private mirror)
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Returns the url of the feeds json for a chain
+    pub fn url(&self, chain: NamedChain) -> Result<String> {
+        let base = self.base_url.trim_end_matches('/');
+        if let Some(location) = self.overrides.get(&chain) {
+            if location.starts_with("http://") || location.starts_with("https://") {
+                return Ok(location.clone());
+            }
+            return Ok(format!("{}/{}", base, location.trim_start_matches('/')));
+        }
+        let file = feeds_file(chain).ok_or(Error::UnsupportedChain(chain.into()))?;
+        Ok(format!("{}/{}", base, file))
+    }
+
+    /// Request for the feeds of a chain with the configured headers
+    pub(crate) fn request(&self, chain: Chain) -> Result<(String, reqwest::RequestBuilder)> {
+        let named = chain.named().ok_or(Error::UnsupportedChain(chain))?;
+        let url = self.url(named)?;
+        let req = reqwest::Client::new()
+            .get(&url)
+            .headers(self.headers.clone());
+        Ok((url, req))
+    }
+
+    /// Downloads the raw feeds json of a chain
+    pub async fn fetch(&self, chain: Chain) -> Result<String> {
+        let (url, req) = self.request(chain)?;
+        let response = req.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::HttpStatus { status, url });
+        }
+        Ok(response.text().await?)
+    }
+}
+
+/// File name of the feeds json in the reference directory
+fn feeds_file(chain: NamedChain) -> Option<&'static str> {
+    let file = match chain {
+        NamedChain::Mainnet => "feeds-mainnet.json",
+        NamedChain::Sepolia => "feeds-ethereum-testnet-sepolia.json",
+        NamedChain::Goerli => "feeds-goerli.json",
+        NamedChain::BinanceSmartChain => "feeds-bsc-mainnet.json",
+        NamedChain::BinanceSmartChainTestnet => "feeds-bsc-testnet.json",
+        NamedChain::Polygon => "feeds-matic-mainnet.json",
+        NamedChain::PolygonMumbai => "feeds-matic-testnet.json",
+        NamedChain::Gnosis => "feeds-xdai-mainnet.json",
+        NamedChain::Avalanche => "feeds-avalanche-mainnet.json",
+        NamedChain::AvalancheFuji => "feeds-avalanche-fuji-testnet.json",
+        NamedChain::Fantom => "feeds-fantom-mainnet.json",
+        NamedChain::FantomTestnet => "feeds-fantom-testnet.json",
+        NamedChain::Arbitrum => "feeds-ethereum-mainnet-arbitrum-1.json",
+        NamedChain::ArbitrumSepolia => "feeds-ethereum-testnet-sepolia-arbitrum-1.json",
+        NamedChain::Optimism => "feeds-ethereum-mainnet-optimism-1.json",
+        NamedChain::OptimismSepolia => "feeds-ethereum-testnet-sepolia-optimism-1.json",
+        NamedChain::OptimismGoerli => "feeds-ethereum-testnet-goerli-optimism-1.json",
+        NamedChain::Moonriver => "feeds-kusama-mainnet-moonriver.json",
+        NamedChain::Moonbeam => "feeds-polkadot-mainnet-moonbeam.json",
+        NamedChain::Metis => "feeds-ethereum-mainnet-andromeda-1.json",
+        NamedChain::Base => "feeds-ethereum-mainnet-base-1.json",
+        NamedChain::BaseGoerli => "feeds-ethereum-testnet-goerli-base-1.json",
+        NamedChain::Celo => "feeds-celo-mainnet.json",
+        NamedChain::CeloAlfajores => "feeds-celo-testnet-alfajores.json",
+        NamedChain::Scroll => "feeds-ethereum-mainnet-scroll-1.json",
+        NamedChain::ScrollSepolia => "feeds-ethereum-testnet-sepolia-scroll-1.json",
+        NamedChain::Linea => "feeds-ethereum-mainnet-linea-1.json",
+        NamedChain::LineaTestnet => "feeds-ethereum-testnet-goerli-linea-1.json",
+        NamedChain::ZkSync => "feeds-ethereum-mainnet-zksync-1.json",
+        NamedChain::ZkSyncTestnet => "feeds-ethereum-testnet-goerli-zksync-1.json",
+        NamedChain::PolygonZkEvm => "feeds-ethereum-mainnet-polygon-zkevm-1.json",
+        NamedChain::PolygonZkEvmTestnet => "feeds-ethereum-testnet-goerli-polygon-zkevm-1.json",
+        _ => return None,
+    };
+    Some(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{serve_http, HttpResponse};
+    use reqwest::header::AUTHORIZATION;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves `[]` for any path, recording (path, authorization header)
+    async fn server() -> (String, Arc<Mutex<Vec<(String, Option<String>)>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let url = serve_http(move |request| {
+            let auth = request.header("authorization").map(str::to_string);
+            recorded.lock().unwrap().push((request.path, auth));
+            HttpResponse::json(&json!([]))
+        })
+        .await;
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn resolves_against_base_url() {
+        let (url, requests) = server().await;
+        let source = ReferenceSource::new(format!("{url}/"));
+        assert_eq!(
+            source.url(NamedChain::Mainnet).unwrap(),
+            format!("{url}/feeds-mainnet.json")
+        );
+        assert_eq!(source.fetch(Chain::mainnet()).await.unwrap(), "[]");
+        // no preview override left on a custom base
+        source
+            .fetch(Chain::from_named(NamedChain::ZkSync))
+            .await
+            .unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                ("/feeds-mainnet.json".to_string(), None),
+                ("/feeds-ethereum-mainnet-zksync-1.json".to_string(), None)
+            ]
+        );
+        assert!(ReferenceSource::default()
+            .url(NamedChain::ZkSync)
+            .unwrap()
+            .starts_with(PREVIEW_BASE_URL));
+    }
+
+    #[tokio::test]
+    async fn overrides_win() {
+        let (url, requests) = server().await;
+        let (mirror, mirrored) = server().await;
+        let source = ReferenceSource::new(&url)
+            .with_override(NamedChain::Mainnet, "/pinned/mainnet.json")
+            .with_override(NamedChain::Sepolia, format!("{mirror}/sepolia.json"));
+        source.fetch(Chain::mainnet()).await.unwrap();
+        source
+            .fetch(Chain::from_named(NamedChain::Sepolia))
+            .await
+            .unwrap();
+        source
+            .fetch(Chain::from_named(NamedChain::Polygon))
+            .await
+            .unwrap();
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                ("/pinned/mainnet.json".to_string(), None),
+                ("/feeds-matic-mainnet.json".to_string(), None)
+            ]
+        );
+        assert_eq!(
+            *mirrored.lock().unwrap(),
+            [("/sepolia.json".to_string(), None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_headers() {
+        let (url, requests) = server().await;
+        ReferenceSource::new(&url)
+            .with_header(
+                AUTHORIZATION,
+                HeaderValue::from_static("Bearer mirror-token"),
+            )
+            .fetch(Chain::mainnet())
+            .await
+            .unwrap();
+        assert_eq!(
+            requests.lock().unwrap()[0].1.as_deref(),
+            Some("Bearer mirror-token")
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_chains() {
+        let (url, requests) = server().await;
+        let source = ReferenceSource::new(&url);
+        assert!(matches!(
+            source.url(NamedChain::Holesky),
+            Err(Error::UnsupportedChain(chain)) if chain == Chain::from_named(NamedChain::Holesky)
+        ));
+        assert!(matches!(
+            source.fetch(Chain::from_id(424_242)).await,
+            Err(Error::UnsupportedChain(_))
+        ));
+        // an override makes any named chain reachable
+        let source = source.with_override(NamedChain::Holesky, "feeds-holesky.json");
+        source
+            .fetch(Chain::from_named(NamedChain::Holesky))
+            .await
+            .unwrap();
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+}
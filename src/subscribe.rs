@@ -0,0 +1,553 @@
+use crate::{
+    contracts::AccessControlledAggregator::AccessControlledAggregator,
+    error::decode,
+    logs::{FeedEvent, RawLog},
+    phases::compose_round_id,
+    rpc::parse_response,
+    unix_now, Error, FeedReader, Result, RoundData,
+};
+use alloy_primitives::{Address, I256};
+use alloy_sol_types::SolEvent;
+use futures::{
+    stream::{self, BoxStream},
+    SinkExt, StreamExt,
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::{
+    net::TcpStream,
+    time::{sleep_until, Instant},
+};
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// How often the proxy is asked for its current phase while subscribed
+pub const DEFAULT_AGGREGATOR_CHECK: Duration = Duration::from_secs(60);
+pub const DEFAULT_RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateSource {
+    /// `AnswerUpdated` log pushed by the node
+    Log,
+    /// `latestRoundData` read through the proxy
+    Poll,
+}
+
+/// New answer of a feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeedUpdate {
+    /// proxy (phase encoded) round id
+    pub round_id: u128,
+    pub answer: I256,
+    pub updated_at: u64,
+    /// block of the log, None for polled rounds
+    pub block_number: Option<u64>,
+    pub source: UpdateSource,
+}
+
+impl FeedUpdate {
+    fn polled(round: &RoundData) -> Self {
+        Self {
+            round_id: round.round_id,
+            answer: round.answer,
+            updated_at: round.updated_at,
+            block_number: None,
+            source: UpdateSource::Poll,
+        }
+    }
+
+    /// Round the update stands for, logs carry no `startedAt` (`updatedAt` stands in)
+    fn round(&self) -> RoundData {
+        RoundData {
+            round_id: self.round_id,
+            answer: self.answer,
+            started_at: self.updated_at,
+            updated_at: self.updated_at,
+            answered_in_round: self.round_id,
+        }
+    }
+}
+
+/// Live updates of a feed
+/// with `ws_url` set: `eth_subscribe("logs")` on the `AnswerUpdated` topic of the current
+/// aggregator, re-subscribing after reconnects and when the proxy moves to a new phase,
+/// while the socket is down `latestRoundData` is polled between reconnect attempts
+/// without: polls `latestRoundData` through the reader's http endpoint
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub reader: FeedReader,
+    pub ws_url: Option<String>,
+    /// feed heartbeat (seconds), polls are brought forward to when the next one is due
+    pub heartbeat: Option<u32>,
+    pub poll_interval: Duration,
+    pub aggregator_check: Duration,
+    pub reconnect_delay: Duration,
+}
+
+impl FeedReader {
+    /// Live updates of the feed, see `Subscription`
+    pub fn subscribe(&self) -> Subscription {
+        Subscription::new(self.clone())
+    }
+}
+
+impl Subscription {
+    pub fn new(reader: FeedReader) -> Self {
+        Self {
+            reader,
+            ws_url: None,
+            heartbeat: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            aggregator_check: DEFAULT_AGGREGATOR_CHECK,
+            reconnect_delay: DEFAULT_RECONNECT_DELAY,
+        }
+    }
+
+    /// Websocket endpoint (`ws://` / `wss://`) of the same chain
+    pub fn ws(mut self, url: impl Into<String>) -> Self {
+        self.ws_url = Some(url.into());
+        self
+    }
+
+    pub fn heartbeat(mut self, seconds: u32) -> Self {
+        self.heartbeat = Some(seconds);
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    pub fn aggregator_check(mut self, interval: Duration) -> Self {
+        self.aggregator_check = interval;
+        self
+    }
+
+    pub fn reconnect_delay(mut self, delay: Duration) -> Self {
+        self.reconnect_delay = delay;
+        self
+    }
+
+    /// Never ending stream of updates, newest round first read on start (and after every
+    /// reconnect, to catch up on what was missed)
+    /// errors are yielded and the stream carries on, reconnecting when needed
+    /// rounds refused by the reader policy are yielded as errors, pushed or polled alike
+    pub fn stream(self) -> BoxStream<'static, Result<FeedUpdate>> {
+        stream::unfold(Listener::new(self), |mut listener| async move {
+            let item = listener.next().await;
+            Some((item, listener))
+        })
+        .boxed()
+    }
+}
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Open subscription
+struct Live {
+    socket: Socket,
+    phase: u16,
+}
+
+enum Wake {
+    Message(Option<Result<Message, tokio_tungstenite::tungstenite::Error>>),
+    Check,
+}
+
+struct Listener {
+    config: Subscription,
+    live: Option<Live>,
+    retry_at: Option<Instant>,
+    next_check: Instant,
+    next_poll: Instant,
+    last_round: Option<u128>,
+}
+
+impl Listener {
+    fn new(config: Subscription) -> Self {
+        let now = Instant::now();
+        Self {
+            next_check: now + config.aggregator_check,
+            config,
+            live: None,
+            retry_at: None,
+            next_poll: now,
+            last_round: None,
+        }
+    }
+
+    async fn next(&mut self) -> Result<FeedUpdate> {
+        match self.config.ws_url.clone() {
+            Some(url) => self.next_log(&url).await,
+            None => self.next_poll().await,
+        }
+    }
+
+    /// Only rounds newer than the last one yielded (ids grow across phases too)
+    fn seen(&mut self, update: FeedUpdate) -> Option<FeedUpdate> {
+        if self.last_round.is_some_and(|last| update.round_id <= last) {
+            return None;
+        }
+        self.last_round = Some(update.round_id);
+        Some(update)
+    }
+
+    async fn next_poll(&mut self) -> Result<FeedUpdate> {
+        loop {
+            if let Some(update) = self.poll_once().await? {
+                return Ok(update);
+            }
+        }
+    }
+
+    /// Reads the latest round once `next_poll` is reached, None if it was yielded already
+    async fn poll_once(&mut self) -> Result<Option<FeedUpdate>> {
+        sleep_until(self.next_poll).await;
+        let round = self.config.reader.latest_round_data().await;
+        self.next_poll = Instant::now() + self.poll_delay(round.as_ref().ok());
+        Ok(self.seen(FeedUpdate::polled(&round?)))
+    }
+
+    /// `poll_interval`, shortened to when the next heartbeat update is due
+    fn poll_delay(&self, round: Option<&RoundData>) -> Duration {
+        let interval = self.config.poll_interval;
+        let (Some(heartbeat), Some(round)) = (self.config.heartbeat, round) else {
+            return interval;
+        };
+        let due = round.updated_at + u64::from(heartbeat);
+        let now = unix_now();
+        if due <= now {
+            // late already, nothing better to do than the regular pace
+            return interval;
+        }
+        interval.min(Duration::from_secs(due - now + 1))
+    }
+
+    async fn next_log(&mut self, url: &str) -> Result<FeedUpdate> {
+        loop {
+            let Some(live) = self.live.as_mut() else {
+                if let Some(at) = self.retry_at {
+                    // disconnected: poll until the next attempt is due
+                    if self.next_poll < at {
+                        if let Some(update) = self.poll_once().await? {
+                            return Ok(update);
+                        }
+                        continue;
+                    }
+                    sleep_until(at).await;
+                    self.retry_at = None;
+                }
+                match self.connect(url).await {
+                    Ok(live) => self.live = Some(live),
+                    Err(e) => {
+                        self.retry_at = Some(Instant::now() + self.config.reconnect_delay);
+                        return Err(e);
+                    }
+                }
+                self.next_check = Instant::now() + self.config.aggregator_check;
+                let round = self.config.reader.latest_round_data().await?;
+                if let Some(update) = self.seen(FeedUpdate::polled(&round)) {
+                    return Ok(update);
+                }
+                continue;
+            };
+
+            let wake = tokio::select! {
+                message = live.socket.next() => Wake::Message(message),
+                _ = sleep_until(self.next_check) => Wake::Check,
+            };
+            let phase = live.phase;
+            match wake {
+                Wake::Message(Some(Ok(Message::Text(text)))) => {
+                    if let Some(update) = parse_notification(&text, phase)? {
+                        // same policy as polled rounds
+                        self.config.reader.check(&update.round())?;
+                        if let Some(update) = self.seen(update) {
+                            return Ok(update);
+                        }
+                    }
+                }
+                Wake::Message(Some(Ok(Message::Close(_)))) | Wake::Message(None) => {
+                    self.live = None;
+                    self.retry_at = Some(Instant::now() + self.config.reconnect_delay);
+                }
+                Wake::Message(Some(Ok(_))) => {}
+                Wake::Message(Some(Err(e))) => {
+                    self.live = None;
+                    self.retry_at = Some(Instant::now() + self.config.reconnect_delay);
+                    return Err(e.into());
+                }
+                Wake::Check => {
+                    self.next_check = Instant::now() + self.config.aggregator_check;
+                    // a confirmed aggregator bumps the phase, subscribe to the new one
+                    if self.config.reader.phase_id().await? != phase {
+                        self.live = None;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Opens the socket and subscribes to the aggregator of the current phase
+    async fn connect(&self, url: &str) -> Result<Live> {
+        let reader = &self.config.reader;
+        let phase = reader.phase_id().await?;
+        let aggregator: Address = reader.phase_aggregator(phase).await?;
+
+        let (mut socket, _) = connect_async(url).await?;
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_subscribe",
+            "params": ["logs", {
+                "address": aggregator,
+                "topics": [AccessControlledAggregator::AnswerUpdated::SIGNATURE_HASH],
+            }],
+        });
+        socket.send(Message::Text(request.to_string())).await?;
+        // notifications only start once the subscription id is returned
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Text(text))) => {
+                    let response: Value = decode(&text)?;
+                    if response.get("id") == Some(&json!(1)) {
+                        let _id: String = parse_response(response)?;
+                        return Ok(Live { socket, phase });
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => {
+                    return Err(Error::InvalidResponse(
+                        "websocket closed before eth_subscribe answered".to_string(),
+                    ))
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// `AnswerUpdated` carried by an `eth_subscription` message, None for anything else
+/// (removed logs included, a reorg dropped them)
+fn parse_notification(text: &str, phase: u16) -> Result<Option<FeedUpdate>> {
+    let mut message: Value = decode(text)?;
+    if message.get("method").and_then(Value::as_str) != Some("eth_subscription") {
+        return Ok(None);
+    }
+    let Some(result) = message.pointer_mut("/params/result").map(Value::take) else {
+        return Ok(None);
+    };
+    let log: RawLog = serde_json::from_value(result).map_err(|source| Error::Decode {
+        path: "params.result".to_string(),
+        source,
+    })?;
+    if log.removed {
+        return Ok(None);
+    }
+    let Some(FeedEvent::AnswerUpdated {
+        current,
+        round_id,
+        updated_at,
+    }) = log.decode()?
+    else {
+        return Ok(None);
+    };
+    Ok(Some(FeedUpdate {
+        round_id: compose_round_id(phase, round_id),
+        answer: current,
+        updated_at,
+        block_number: Some(log.block_number),
+        source: UpdateSource::Log,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{answer_log, MockFeed, MockNode, MockSocket};
+    use alloy_primitives::{address, B256};
+    use std::sync::{Arc, Mutex};
+
+    const HOUR: Duration = Duration::from_secs(3_600);
+
+    async fn node(feed: &Arc<Mutex<MockFeed>>) -> MockNode {
+        let feed = feed.clone();
+        MockNode::start(move |_, params| feed.lock().unwrap().eth_call(params)).await
+    }
+
+    fn feed() -> Arc<Mutex<MockFeed>> {
+        Arc::new(Mutex::new(MockFeed {
+            proxy: address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419"),
+            phases: vec![(Address::repeat_byte(1), vec![100])],
+        }))
+    }
+
+    /// (proxy round id, source) of the next update
+    async fn next(updates: &mut BoxStream<'static, Result<FeedUpdate>>) -> (u128, UpdateSource) {
+        let update = updates.next().await.unwrap().unwrap();
+        (update.round_id, update.source)
+    }
+
+    #[tokio::test]
+    async fn resubscribes_after_drops() {
+        let feed = feed();
+        let node = node(&feed).await;
+        let socket = MockSocket::start().await;
+        let proxy = feed.lock().unwrap().proxy;
+        let mut updates = FeedReader::new(&node.url, proxy)
+            .subscribe()
+            .ws(&socket.url)
+            .poll_interval(HOUR)
+            .aggregator_check(HOUR)
+            .reconnect_delay(Duration::from_millis(10))
+            .stream();
+
+        // catch up read, then the pushed log
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 1), UpdateSource::Poll)
+        );
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 2), UpdateSource::Log)
+        );
+
+        // aggregator confirmed while the socket is down
+        feed.lock()
+            .unwrap()
+            .phases
+            .push((Address::repeat_byte(2), vec![300]));
+        socket.close_connection();
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(2, 1), UpdateSource::Poll)
+        );
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(2, 2), UpdateSource::Log)
+        );
+        assert_eq!(
+            socket.subscribed(),
+            vec![Address::repeat_byte(1), Address::repeat_byte(2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn follows_phase_changes() {
+        let feed = feed();
+        let node = node(&feed).await;
+        let socket = MockSocket::start().await;
+        let proxy = feed.lock().unwrap().proxy;
+        let mut updates = FeedReader::new(&node.url, proxy)
+            .subscribe()
+            .ws(&socket.url)
+            .poll_interval(HOUR)
+            .aggregator_check(Duration::from_millis(20))
+            .stream();
+
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 1), UpdateSource::Poll)
+        );
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 2), UpdateSource::Log)
+        );
+        feed.lock()
+            .unwrap()
+            .phases
+            .push((Address::repeat_byte(2), vec![300]));
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(2, 1), UpdateSource::Poll)
+        );
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(2, 2), UpdateSource::Log)
+        );
+        assert_eq!(socket.subscribed()[1], Address::repeat_byte(2));
+    }
+
+    #[tokio::test]
+    async fn polls_while_disconnected() {
+        let feed = feed();
+        let node = node(&feed).await;
+        // nothing listens there
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let proxy = feed.lock().unwrap().proxy;
+        let mut updates = FeedReader::new(&node.url, proxy)
+            .subscribe()
+            .ws(url)
+            .poll_interval(Duration::from_millis(10))
+            .reconnect_delay(HOUR)
+            .stream();
+
+        assert!(matches!(
+            updates.next().await,
+            Some(Err(Error::WebSocket(_)))
+        ));
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 1), UpdateSource::Poll)
+        );
+        feed.lock().unwrap().phases[0].1.push(200);
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 2), UpdateSource::Poll)
+        );
+    }
+
+    #[tokio::test]
+    async fn applies_the_reader_policy_to_logs() {
+        let feed = feed();
+        let node = node(&feed).await;
+        let socket = MockSocket::start_with(|aggregator| {
+            let mut log = answer_log(aggregator, 2, 100, B256::ZERO);
+            log["topics"][1] = json!(B256::ZERO);
+            log
+        })
+        .await;
+        let proxy = feed.lock().unwrap().proxy;
+        let mut updates = FeedReader::new(&node.url, proxy)
+            .with_policy(crate::RoundPolicy::default())
+            .subscribe()
+            .ws(&socket.url)
+            .poll_interval(HOUR)
+            .aggregator_check(HOUR)
+            .stream();
+
+        assert_eq!(
+            next(&mut updates).await,
+            (compose_round_id(1, 1), UpdateSource::Poll)
+        );
+        // a zero answer is refused whichever way it arrives
+        assert!(matches!(
+            updates.next().await,
+            Some(Err(Error::InvalidRound {
+                issue: crate::RoundIssue::ZeroAnswer,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn notifications() {
+        let log = answer_log(Address::repeat_byte(1), 7, 100, B256::ZERO);
+        let message = |log: &Value| {
+            json!({"method": "eth_subscription", "params": {"result": log}}).to_string()
+        };
+        let update = parse_notification(&message(&log), 3).unwrap().unwrap();
+        assert_eq!(update.round_id, compose_round_id(3, 7));
+        assert_eq!(update.answer, I256::try_from(700).unwrap());
+        assert_eq!((update.updated_at, update.block_number), (1_007, Some(100)));
+
+        let mut removed = log.clone();
+        removed["removed"] = json!(true);
+        assert_eq!(parse_notification(&message(&removed), 3).unwrap(), None);
+        let response = json!({"id": 1, "result": "0x1"}).to_string();
+        assert_eq!(parse_notification(&response, 3).unwrap(), None);
+    }
+}
@@ -0,0 +1,398 @@
+//! Local JSON-RPC node stand-in for unit tests
+
+use crate::{
+    contracts::{
+        AccessControlledAggregator::AccessControlledAggregator,
+        AggregatorContract::AggregatorContract, EACAggregatorProxy::EACAggregatorProxy,
+    },
+    phases::{compose_round_id, split_round_id},
+};
+use alloy_primitives::{hex, Address, B256, I256, U256};
+use alloy_sol_types::{SolCall, SolEvent};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+};
+use tokio_tungstenite::{accept_async, tungstenite::Message};
+
+/// Request read by `serve_http`
+pub(crate) struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+pub(crate) struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn json(body: &Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("content-type", "application/json".to_string())],
+            body: body.to_string(),
+        }
+    }
+}
+
+type Handler = dyn Fn(HttpRequest) -> HttpResponse + Send + Sync;
+
+/// Answers http/1.1 with `handler` on a local port, returns the base url
+pub(crate) async fn serve_http<F>(handler: F) -> String
+where
+    F: Fn(HttpRequest) -> HttpResponse + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler: Arc<Handler> = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream, handler.clone()));
+        }
+    });
+    url
+}
+
+/// Answers JSON-RPC over http on a local port, every request body is recorded
+pub(crate) struct MockNode {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+}
+
+impl MockNode {
+    /// `handler` gets one request object and returns its `result`, or `(code, message)`
+    /// for an error object, batches are answered entry by entry in order
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &Value) -> Result<Value, (i64, String)> + Send + Sync + 'static,
+    {
+        Self::raw(move |body| match body {
+            Value::Array(requests) => requests.iter().map(|r| answer(&handler, r)).collect(),
+            request => answer(&handler, &request),
+        })
+        .await
+    }
+
+    /// `handler` gets the whole request body and returns the whole response body
+    pub async fn raw<F>(handler: F) -> Self
+    where
+        F: Fn(Value) -> Value + Send + Sync + 'static,
+    {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let url = serve_http(move |request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+            recorded.lock().unwrap().push(body.clone());
+            HttpResponse::json(&handler(body))
+        })
+        .await;
+        Self { url, requests }
+    }
+
+    /// Request bodies received so far
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn answer<F>(handler: &F, request: &Value) -> Value
+where
+    F: Fn(&str, &Value) -> Result<Value, (i64, String)>,
+{
+    let method = request["method"].as_str().unwrap_or_default();
+    match handler(method, &request["params"]) {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": {"code": code, "message": message},
+        }),
+    }
+}
+
+/// Keep-alive http/1.1
+async fn serve(stream: TcpStream, handler: Arc<Handler>) {
+    let mut stream = BufReader::new(stream);
+    loop {
+        let mut start = String::new();
+        if stream.read_line(&mut start).await.unwrap_or(0) == 0 {
+            return;
+        }
+        let mut parts = start.split_whitespace();
+        let (method, path) = match (parts.next(), parts.next()) {
+            (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+            _ => return,
+        };
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).await.unwrap_or(0) == 0 {
+                return;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.to_string(), value.trim().to_string()));
+            }
+        }
+        let length = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.parse().ok())
+            .unwrap_or(0);
+        let mut body = vec![0; length];
+        if stream.read_exact(&mut body).await.is_err() {
+            return;
+        }
+        let response = handler(HttpRequest {
+            method,
+            path,
+            headers,
+            body,
+        });
+        let mut message = format!("HTTP/1.1 {} -\r\n", response.status);
+        for (name, value) in &response.headers {
+            message.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        // one write, a separate head waits on the peer's delayed ack
+        message.push_str(&format!(
+            "content-length: {}\r\n\r\n{}",
+            response.body.len(),
+            response.body
+        ));
+        if stream
+            .get_mut()
+            .write_all(message.as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// `to` and calldata of `eth_call` params
+pub(crate) fn call_args(params: &Value) -> (Address, Vec<u8>) {
+    let to = params[0]["to"].as_str().unwrap().parse().unwrap();
+    let data = hex::decode(params[0]["data"].as_str().unwrap()).unwrap();
+    (to, data)
+}
+
+/// Hex string result of an `eth_call`
+pub(crate) fn output(bytes: Vec<u8>) -> Value {
+    json!(hex::encode_prefixed(bytes))
+}
+
+/// Error object of a reverted call, as geth sends it
+pub(crate) fn reverted() -> (i64, String) {
+    (3, "execution reverted".to_string())
+}
+
+/// EACAggregatorProxy over a few phases, round `n` of a phase was updated at
+/// `updated_at[n - 1]` and answered that same number
+#[derive(Debug, Clone)]
+pub(crate) struct MockFeed {
+    pub proxy: Address,
+    /// aggregator and round timestamps of phase 1, 2..
+    pub phases: Vec<(Address, Vec<u64>)>,
+}
+
+impl MockFeed {
+    /// Answers `eth_call` params aimed at the proxy or one of its aggregators,
+    /// `getRoundData` of an unknown round reverts
+    pub fn eth_call(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (to, data) = call_args(params);
+        let selector: [u8; 4] = data[..4].try_into().unwrap();
+        if to != self.proxy {
+            let (_, rounds) = self.phases.iter().find(|(a, _)| *a == to).unwrap();
+            assert_eq!(selector, AggregatorContract::latestRoundCall::SELECTOR);
+            let latest = U256::from(rounds.len());
+            return Ok(output(
+                AggregatorContract::latestRoundCall::abi_encode_returns(&(latest,)),
+            ));
+        }
+        let phase = self.phases.len() as u16;
+        let out = match selector {
+            EACAggregatorProxy::phaseIdCall::SELECTOR => {
+                EACAggregatorProxy::phaseIdCall::abi_encode_returns(&(phase,))
+            }
+            EACAggregatorProxy::phaseAggregatorsCall::SELECTOR => {
+                let call =
+                    EACAggregatorProxy::phaseAggregatorsCall::abi_decode(&data, true).unwrap();
+                let aggregator = match usize::from(call._0).checked_sub(1) {
+                    Some(i) => self.phases.get(i).map_or(Address::ZERO, |(a, _)| *a),
+                    None => Address::ZERO,
+                };
+                EACAggregatorProxy::phaseAggregatorsCall::abi_encode_returns(&(aggregator,))
+            }
+            EACAggregatorProxy::aggregatorCall::SELECTOR => {
+                let aggregator = self.phases.last().map_or(Address::ZERO, |(a, _)| *a);
+                EACAggregatorProxy::aggregatorCall::abi_encode_returns(&(aggregator,))
+            }
+            EACAggregatorProxy::decimalsCall::SELECTOR => {
+                EACAggregatorProxy::decimalsCall::abi_encode_returns(&(8u8,))
+            }
+            EACAggregatorProxy::latestRoundDataCall::SELECTOR => {
+                let latest = self.phases.last().map_or(0, |(_, r)| r.len() as u64);
+                let (round_id, updated_at) = self.round(compose_round_id(phase, latest))?;
+                EACAggregatorProxy::latestRoundDataCall::abi_encode_returns(&round_values(
+                    round_id, updated_at,
+                ))
+            }
+            EACAggregatorProxy::getRoundDataCall::SELECTOR => {
+                let call = EACAggregatorProxy::getRoundDataCall::abi_decode(&data, true).unwrap();
+                let (round_id, updated_at) = self.round(call._roundId)?;
+                EACAggregatorProxy::getRoundDataCall::abi_encode_returns(&round_values(
+                    round_id, updated_at,
+                ))
+            }
+            _ => return Err(reverted()),
+        };
+        Ok(output(out))
+    }
+
+    fn round(&self, round_id: u128) -> Result<(u128, u64), (i64, String)> {
+        let (phase, round) = split_round_id(round_id);
+        usize::from(phase)
+            .checked_sub(1)
+            .and_then(|i| self.phases.get(i))
+            .and_then(|(_, rounds)| rounds.get((round as usize).checked_sub(1)?))
+            .map(|updated_at| (round_id, *updated_at))
+            .ok_or_else(reverted)
+    }
+}
+
+fn round_values(round_id: u128, updated_at: u64) -> (u128, I256, U256, U256, u128) {
+    let timestamp = U256::from(updated_at);
+    let answer = I256::try_from(updated_at).unwrap();
+    (round_id, answer, timestamp, timestamp, round_id)
+}
+
+/// `AnswerUpdated` of aggregator round `round` as `eth_getLogs` returns it, answered
+/// `round * 100` at `1000 + round`
+pub(crate) fn answer_log(aggregator: Address, round: u64, block: u64, block_hash: B256) -> Value {
+    json!({
+        "address": aggregator,
+        "topics": [
+            AccessControlledAggregator::AnswerUpdated::SIGNATURE_HASH,
+            B256::from(U256::from(round * 100)),
+            B256::from(U256::from(round)),
+        ],
+        "data": hex::encode_prefixed(B256::from(U256::from(1000 + round))),
+        "blockNumber": format!("{:#x}", block),
+        "blockHash": block_hash,
+        "transactionHash": B256::repeat_byte(0xee),
+        "logIndex": "0x0",
+        "removed": false,
+    })
+}
+
+/// Websocket node taking `eth_subscribe("logs")`, every subscription is sent the
+/// `AnswerUpdated` of round 2 of the subscribed aggregator (or the log of `start_with`)
+pub(crate) struct MockSocket {
+    pub url: String,
+    subscribed: Arc<Mutex<Vec<Address>>>,
+    close: Arc<Notify>,
+}
+
+impl MockSocket {
+    pub async fn start() -> Self {
+        Self::start_with(|aggregator| answer_log(aggregator, 2, 100, B256::ZERO)).await
+    }
+
+    /// Sends `log(aggregator)` to every subscription
+    pub async fn start_with(log: fn(Address) -> Value) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let subscribed = Arc::new(Mutex::new(Vec::new()));
+        let close = Arc::new(Notify::new());
+        let (recorded, notify) = (subscribed.clone(), close.clone());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_socket(stream, log, recorded.clone(), notify.clone()));
+            }
+        });
+        Self {
+            url,
+            subscribed,
+            close,
+        }
+    }
+
+    /// Aggregators subscribed to so far
+    pub fn subscribed(&self) -> Vec<Address> {
+        self.subscribed.lock().unwrap().clone()
+    }
+
+    /// Closes one open connection
+    pub fn close_connection(&self) {
+        self.close.notify_one();
+    }
+}
+
+async fn serve_socket(
+    stream: TcpStream,
+    log: fn(Address) -> Value,
+    subscribed: Arc<Mutex<Vec<Address>>>,
+    close: Arc<Notify>,
+) {
+    let Ok(mut socket) = accept_async(stream).await else {
+        return;
+    };
+    let Some(Ok(Message::Text(text))) = socket.next().await else {
+        return;
+    };
+    let request: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(request["method"], "eth_subscribe");
+    assert_eq!(request["params"][0], "logs");
+    assert_eq!(
+        request["params"][1]["topics"],
+        json!([AccessControlledAggregator::AnswerUpdated::SIGNATURE_HASH])
+    );
+    let aggregator: Address =
+        serde_json::from_value(request["params"][1]["address"].clone()).unwrap();
+    subscribed.lock().unwrap().push(aggregator);
+
+    let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"});
+    let notification = json!({
+        "jsonrpc": "2.0",
+        "method": "eth_subscription",
+        "params": {"subscription": "0x1", "result": log(aggregator)},
+    });
+    for message in [response, notification] {
+        if socket
+            .send(Message::Text(message.to_string()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+    // until told to close, or the client leaves
+    let closed = tokio::select! {
+        _ = close.notified() => true,
+        _ = async { while let Some(Ok(_)) = socket.next().await {} } => false,
+    };
+    if closed {
+        let _ = socket.close(None).await;
+    }
+}
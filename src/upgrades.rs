@@ -0,0 +1,325 @@
+use crate::{
+    contracts::EACAggregatorProxy::EACAggregatorProxy, multicall::decode, BatchReader, Error,
+    Result,
+};
+use alloy_primitives::{Address, Bytes};
+use alloy_sol_types::SolCall;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
+use std::{collections::HashMap, time::Duration};
+
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What the proxy points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProxyState {
+    pub aggregator: Address,
+    pub phase_id: u16,
+    /// aggregator waiting for `confirmAggregator`
+    pub proposed: Option<Address>,
+}
+
+/// Change noticed on a proxy between two polls
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpgradeEvent {
+    /// `proposeAggregator`, also reported for a proposal pending on the first poll
+    AggregatorProposed { proxy: Address, aggregator: Address },
+    /// pending proposal dropped (or replaced) without being confirmed
+    ProposalWithdrawn { proxy: Address, aggregator: Address },
+    /// `confirmAggregator`, the feed now reads from `aggregator`
+    AggregatorConfirmed {
+        proxy: Address,
+        previous: Address,
+        aggregator: Address,
+    },
+    PhaseChanged {
+        proxy: Address,
+        previous: u16,
+        phase: u16,
+    },
+}
+
+/// Polls `aggregator()`, `phaseId()` and `proposedAggregator()` of many proxies
+/// (one multicall per poll) and reports what changed
+#[derive(Debug, Clone)]
+pub struct UpgradeWatcher {
+    pub batch: BatchReader,
+    pub proxies: Vec<Address>,
+    pub poll_interval: Duration,
+    states: HashMap<Address, ProxyState>,
+}
+
+impl UpgradeWatcher {
+    pub fn new(batch: BatchReader, proxies: Vec<Address>) -> Self {
+        Self {
+            batch,
+            proxies,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            states: HashMap::new(),
+        }
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Last state read for `proxy`
+    pub fn state(&self, proxy: Address) -> Option<&ProxyState> {
+        self.states.get(&proxy)
+    }
+
+    /// Reads every proxy once, the first read of a proxy is its baseline
+    /// proxies whose calls fail (ie: not an EACAggregatorProxy) keep their previous state
+    pub async fn poll(&mut self) -> Result<Vec<UpgradeEvent>> {
+        let mut calls = Vec::with_capacity(self.proxies.len() * 3);
+        for proxy in &self.proxies {
+            calls.push((*proxy, EACAggregatorProxy::aggregatorCall {}.abi_encode()));
+            calls.push((*proxy, EACAggregatorProxy::phaseIdCall {}.abi_encode()));
+            calls.push((
+                *proxy,
+                EACAggregatorProxy::proposedAggregatorCall {}.abi_encode(),
+            ));
+        }
+        let mut outputs = self.batch.call_raw(&calls).await?.into_iter();
+
+        let mut events = Vec::new();
+        for proxy in &self.proxies {
+            let (Some(aggregator), Some(phase_id), Some(proposed)) =
+                (outputs.next(), outputs.next(), outputs.next())
+            else {
+                return Err(Error::InvalidResponse("missing batch output".to_string()));
+            };
+            let Ok(state) = read_state(*proxy, aggregator, phase_id, proposed) else {
+                continue;
+            };
+            let previous = self.states.insert(*proxy, state);
+            events.extend(changes(*proxy, previous.as_ref(), &state));
+        }
+        Ok(events)
+    }
+
+    /// Polls every `poll_interval` forever, errors are yielded and polling goes on
+    pub fn stream(self) -> BoxStream<'static, Result<UpgradeEvent>> {
+        stream::unfold((self, true), |(mut watcher, first)| async move {
+            if !first {
+                tokio::time::sleep(watcher.poll_interval).await;
+            }
+            let events = watcher
+                .poll()
+                .await
+                .map(|events| stream::iter(events.into_iter().map(Ok)));
+            Some((events, (watcher, false)))
+        })
+        .try_flatten()
+        .boxed()
+    }
+}
+
+fn read_state(
+    proxy: Address,
+    aggregator: Result<Bytes>,
+    phase_id: Result<Bytes>,
+    proposed: Result<Bytes>,
+) -> Result<ProxyState> {
+    let proposed = decode::<EACAggregatorProxy::proposedAggregatorCall>(proxy, proposed)?._0;
+    Ok(ProxyState {
+        aggregator: decode::<EACAggregatorProxy::aggregatorCall>(proxy, aggregator)?._0,
+        phase_id: decode::<EACAggregatorProxy::phaseIdCall>(proxy, phase_id)?._0,
+        proposed: (proposed != Address::ZERO).then_some(proposed),
+    })
+}
+
+/// Events between two reads of a proxy, without `previous` (baseline) only a pending
+/// proposal is reported
+fn changes(proxy: Address, previous: Option<&ProxyState>, state: &ProxyState) -> Vec<UpgradeEvent> {
+    let mut events = Vec::new();
+    let previous_proposed = previous.and_then(|p| p.proposed);
+    if let Some(aggregator) =
+        previous_proposed.filter(|p| state.proposed != Some(*p) && state.aggregator != *p)
+    {
+        events.push(UpgradeEvent::ProposalWithdrawn { proxy, aggregator });
+    }
+    if let Some(aggregator) = state.proposed.filter(|p| previous_proposed != Some(*p)) {
+        events.push(UpgradeEvent::AggregatorProposed { proxy, aggregator });
+    }
+    let Some(previous) = previous else {
+        return events;
+    };
+    if state.aggregator != previous.aggregator {
+        events.push(UpgradeEvent::AggregatorConfirmed {
+            proxy,
+            previous: previous.aggregator,
+            aggregator: state.aggregator,
+        });
+    }
+    if state.phase_id != previous.phase_id {
+        events.push(UpgradeEvent::PhaseChanged {
+            proxy,
+            previous: previous.phase_id,
+            phase: state.phase_id,
+        });
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{call_args, output, reverted, MockNode};
+    use alloy_primitives::address;
+    use std::sync::{Arc, Mutex};
+
+    const PROXY: Address = address!("5f4eC3Df9cbd43714FE2740f5E3616155c5b8419");
+
+    fn state(aggregator: u8, phase_id: u16, proposed: Option<u8>) -> ProxyState {
+        ProxyState {
+            aggregator: Address::repeat_byte(aggregator),
+            phase_id,
+            proposed: proposed.map(Address::repeat_byte),
+        }
+    }
+
+    #[test]
+    fn read_states() {
+        let ok = |bytes: Vec<u8>| Ok(Bytes::from(bytes));
+        let aggregator = || {
+            ok(EACAggregatorProxy::aggregatorCall::abi_encode_returns(&(
+                Address::repeat_byte(1),
+            )))
+        };
+        let phase_id = || {
+            ok(EACAggregatorProxy::phaseIdCall::abi_encode_returns(
+                &(3u16,),
+            ))
+        };
+        let proposed =
+            |a| ok(EACAggregatorProxy::proposedAggregatorCall::abi_encode_returns(&(a,)));
+
+        assert_eq!(
+            read_state(PROXY, aggregator(), phase_id(), proposed(Address::ZERO)).unwrap(),
+            state(1, 3, None)
+        );
+        assert_eq!(
+            read_state(
+                PROXY,
+                aggregator(),
+                phase_id(),
+                proposed(Address::repeat_byte(2))
+            )
+            .unwrap(),
+            state(1, 3, Some(2))
+        );
+        let revert = Err(Error::Rpc {
+            code: 3,
+            message: "execution reverted".to_string(),
+        });
+        assert!(read_state(PROXY, aggregator(), revert, proposed(Address::ZERO)).is_err());
+        assert!(matches!(
+            read_state(PROXY, ok(Vec::new()), phase_id(), proposed(Address::ZERO)),
+            Err(Error::EmptyOutput { to: PROXY })
+        ));
+    }
+
+    #[test]
+    fn change_events() {
+        let proposed = |a: u8| UpgradeEvent::AggregatorProposed {
+            proxy: PROXY,
+            aggregator: Address::repeat_byte(a),
+        };
+        let withdrawn = |a: u8| UpgradeEvent::ProposalWithdrawn {
+            proxy: PROXY,
+            aggregator: Address::repeat_byte(a),
+        };
+
+        // baseline: only a pending proposal
+        assert_eq!(changes(PROXY, None, &state(1, 1, None)), []);
+        assert_eq!(changes(PROXY, None, &state(1, 1, Some(2))), [proposed(2)]);
+
+        let before = state(1, 1, Some(2));
+        assert_eq!(changes(PROXY, Some(&before), &before), []);
+        assert_eq!(
+            changes(PROXY, Some(&state(1, 1, None)), &before),
+            [proposed(2)]
+        );
+        assert_eq!(
+            changes(PROXY, Some(&before), &state(2, 2, None)),
+            [
+                UpgradeEvent::AggregatorConfirmed {
+                    proxy: PROXY,
+                    previous: Address::repeat_byte(1),
+                    aggregator: Address::repeat_byte(2),
+                },
+                UpgradeEvent::PhaseChanged {
+                    proxy: PROXY,
+                    previous: 1,
+                    phase: 2
+                },
+            ]
+        );
+        assert_eq!(
+            changes(PROXY, Some(&before), &state(1, 1, None)),
+            [withdrawn(2)]
+        );
+        assert_eq!(
+            changes(PROXY, Some(&before), &state(1, 1, Some(3))),
+            [withdrawn(2), proposed(3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn polls() {
+        let current = Arc::new(Mutex::new(state(1, 1, Some(2))));
+        let proxy_state = current.clone();
+        let node = MockNode::start(move |_, params| {
+            let (to, data) = call_args(params);
+            if to != PROXY {
+                return Err(reverted());
+            }
+            let state = *proxy_state.lock().unwrap();
+            let out = match data[..4].try_into().unwrap() {
+                EACAggregatorProxy::aggregatorCall::SELECTOR => {
+                    EACAggregatorProxy::aggregatorCall::abi_encode_returns(&(state.aggregator,))
+                }
+                EACAggregatorProxy::phaseIdCall::SELECTOR => {
+                    EACAggregatorProxy::phaseIdCall::abi_encode_returns(&(state.phase_id,))
+                }
+                _ => EACAggregatorProxy::proposedAggregatorCall::abi_encode_returns(&(state
+                    .proposed
+                    .unwrap_or(Address::ZERO),)),
+            };
+            Ok(output(out))
+        })
+        .await;
+        // the second address is not a proxy, its reads fail and it is skipped
+        let batch = BatchReader::new(&node.url).without_multicall();
+        let mut watcher = UpgradeWatcher::new(batch, vec![PROXY, Address::repeat_byte(9)]);
+
+        let events = watcher.poll().await.unwrap();
+        assert_eq!(
+            events,
+            [UpgradeEvent::AggregatorProposed {
+                proxy: PROXY,
+                aggregator: Address::repeat_byte(2)
+            }]
+        );
+        assert_eq!(watcher.state(PROXY), Some(&state(1, 1, Some(2))));
+        assert_eq!(watcher.state(Address::repeat_byte(9)), None);
+        assert_eq!(watcher.poll().await.unwrap(), []);
+
+        *current.lock().unwrap() = state(2, 2, None);
+        assert_eq!(watcher.poll().await.unwrap().len(), 2);
+        *current.lock().unwrap() = state(2, 2, Some(3));
+        watcher.poll().await.unwrap();
+        *current.lock().unwrap() = state(2, 2, None);
+        assert_eq!(
+            watcher.poll().await.unwrap(),
+            [UpgradeEvent::ProposalWithdrawn {
+                proxy: PROXY,
+                aggregator: Address::repeat_byte(3)
+            }]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{Error, Result, RoundData};
+use alloy_primitives::I256;
+use std::fmt;
+
+/// Reason a round should not be consumed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundIssue {
+    ZeroAnswer,
+    NegativeAnswer,
+    /// `updatedAt == 0`
+    NotUpdated,
+    /// `answeredInRound < roundId`, answer carried over from an older round
+    Incomplete,
+}
+
+impl fmt::Display for RoundIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RoundIssue::ZeroAnswer => "answer is zero",
+            RoundIssue::NegativeAnswer => "answer is negative",
+            RoundIssue::NotUpdated => "round was never updated",
+            RoundIssue::Incomplete => "answered in an older round",
+        })
+    }
+}
+
+/// Which rounds are acceptable, default follows Chainlink consumer guidelines
+/// (positive answer, updated, complete)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundPolicy {
+    pub allow_zero: bool,
+    /// for feeds that can legitimately go below zero (rates, spreads..)
+    pub allow_negative: bool,
+    pub require_complete: bool,
+}
+
+impl Default for RoundPolicy {
+    fn default() -> Self {
+        Self {
+            allow_zero: false,
+            allow_negative: false,
+            require_complete: true,
+        }
+    }
+}
+
+impl RoundPolicy {
+    /// Accepts any round that was updated
+    pub fn permissive() -> Self {
+        Self {
+            allow_zero: true,
+            allow_negative: true,
+            require_complete: false,
+        }
+    }
+
+    pub fn allow_zero(mut self, allow: bool) -> Self {
+        self.allow_zero = allow;
+        self
+    }
+
+    pub fn allow_negative(mut self, allow: bool) -> Self {
+        self.allow_negative = allow;
+        self
+    }
+
+    pub fn require_complete(mut self, require: bool) -> Self {
+        self.require_complete = require;
+        self
+    }
+
+    /// Every rule of the policy `round` breaks
+    pub fn issues(&self, round: &RoundData) -> Vec<RoundIssue> {
+        let mut issues = Vec::new();
+        if round.updated_at == 0 {
+            issues.push(RoundIssue::NotUpdated);
+        }
+        if round.answer.is_zero() && !self.allow_zero {
+            issues.push(RoundIssue::ZeroAnswer);
+        }
+        if round.answer < I256::ZERO && !self.allow_negative {
+            issues.push(RoundIssue::NegativeAnswer);
+        }
+        if self.require_complete && round.answered_in_round < round.round_id {
+            issues.push(RoundIssue::Incomplete);
+        }
+        issues
+    }
+
+    pub fn check(&self, round: &RoundData) -> Result<()> {
+        match self.issues(round).first() {
+            Some(issue) => Err(Error::InvalidRound {
+                round_id: round.round_id,
+                issue: *issue,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl RoundData {
+    /// Checks the round against the default `RoundPolicy`
+    pub fn validate(&self) -> Result<()> {
+        RoundPolicy::default().check(self)
+    }
+
+    pub fn validate_with(&self, policy: &RoundPolicy) -> Result<()> {
+        policy.check(self)
+    }
+
+    pub fn is_valid(&self, policy: &RoundPolicy) -> bool {
+        policy.issues(self).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(answer: i64, updated_at: u64, answered_in_round: u128) -> RoundData {
+        RoundData {
+            round_id: 10,
+            answer: I256::try_from(answer).unwrap(),
+            started_at: updated_at,
+            updated_at,
+            answered_in_round,
+        }
+    }
+
+    #[test]
+    fn issues() {
+        let policy = RoundPolicy::default();
+        assert!(policy.issues(&round(100, 1000, 10)).is_empty());
+        assert!(policy.issues(&round(100, 1000, 11)).is_empty());
+        assert_eq!(policy.issues(&round(0, 1000, 10)), [RoundIssue::ZeroAnswer]);
+        assert_eq!(
+            policy.issues(&round(-1, 1000, 10)),
+            [RoundIssue::NegativeAnswer]
+        );
+        assert_eq!(
+            policy.issues(&round(100, 1000, 9)),
+            [RoundIssue::Incomplete]
+        );
+        // every broken rule, not only the first one
+        assert_eq!(
+            policy.issues(&round(0, 0, 0)),
+            [
+                RoundIssue::NotUpdated,
+                RoundIssue::ZeroAnswer,
+                RoundIssue::Incomplete
+            ]
+        );
+
+        let relaxed = policy
+            .allow_zero(true)
+            .allow_negative(true)
+            .require_complete(false);
+        assert_eq!(relaxed, RoundPolicy::permissive());
+        assert!(relaxed.issues(&round(-1, 1000, 0)).is_empty());
+        assert!(relaxed.issues(&round(0, 1000, 0)).is_empty());
+        // a round that was never updated is refused by any policy
+        assert_eq!(relaxed.issues(&round(100, 0, 10)), [RoundIssue::NotUpdated]);
+    }
+
+    #[test]
+    fn check() {
+        assert!(round(100, 1000, 10).validate().is_ok());
+        assert!(matches!(
+            round(0, 0, 10).validate(),
+            Err(Error::InvalidRound {
+                round_id: 10,
+                issue: RoundIssue::NotUpdated
+            })
+        ));
+        assert!(round(-5, 1000, 10)
+            .validate_with(&RoundPolicy::default().allow_negative(true))
+            .is_ok());
+        assert!(!round(-5, 1000, 10).is_valid(&RoundPolicy::default()));
+    }
+}